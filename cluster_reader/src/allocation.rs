@@ -0,0 +1,284 @@
+pub mod allocation {
+
+    use std::collections::BTreeMap;
+    use serde::{Deserialize, Serialize};
+
+    use crate::kube_model::kube_model::{Container, Node, Pod};
+    use crate::quantity::quantity::Quantity;
+
+    // What the pods on a node have asked for, the same totals as the "Allocated resources" table of
+    // kubectl describe node. CPU is in millicores and memory in bytes, percentages are of the node's
+    // allocatable, rounded down, and null when the node doesn't report it. Limits can pass 100%.
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct AllocatedResources {
+        cpu: AllocatedAmount,
+        memory: AllocatedAmount,
+        // The non-terminated pods on the node
+        pods: Vec<PodResources>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct AllocatedAmount {
+        requests: i64,
+        requests_percent: Option<i64>,
+        limits: i64,
+        limits_percent: Option<i64>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct PodResources {
+        namespace: String,
+        name: String,
+        cpu_requests: i64,
+        cpu_limits: i64,
+        memory_requests: i64,
+        memory_limits: i64,
+    }
+
+    #[derive(Clone, Copy)]
+    enum Resource {
+        Cpu,
+        Memory,
+    }
+
+    impl Resource {
+
+        fn name(&self) -> &'static str {
+            match self {
+                Resource::Cpu => "cpu",
+                Resource::Memory => "memory",
+            }
+        }
+
+        // Unparseable or missing values count as nothing, as the scheduler would never have placed
+        // a pod with an invalid quantity
+        fn amount(&self, resources: &BTreeMap<String, String>) -> i64 {
+            resources
+                .get(self.name())
+                .and_then(|value| Quantity::parse(value))
+                .and_then(|quantity| match self {
+                    Resource::Cpu => quantity.milli_value(),
+                    Resource::Memory => quantity.value(),
+                })
+                .unwrap_or(0)
+        }
+    }
+
+    // `pods` are the pods scheduled on the node
+    pub fn process_allocated_resources(node: &Node, pods: &[Pod]) -> AllocatedResources {
+
+        let mut pods: Vec<PodResources> = pods
+            .iter()
+            .filter(|pod| !pod.is_terminated())
+            .map(|pod| PodResources {
+                namespace: pod.metadata.namespace.clone().unwrap_or_default(),
+                name: String::from(&pod.metadata.name),
+                cpu_requests: pod_amount(pod, Resource::Cpu, false),
+                cpu_limits: pod_amount(pod, Resource::Cpu, true),
+                memory_requests: pod_amount(pod, Resource::Memory, false),
+                memory_limits: pod_amount(pod, Resource::Memory, true),
+            })
+            .collect();
+
+        pods.sort_by(|left, right| (&left.namespace, &left.name).cmp(&(&right.namespace, &right.name)));
+
+        let cpu_allocatable = Resource::Cpu.amount(&node.status.allocatable);
+        let memory_allocatable = Resource::Memory.amount(&node.status.allocatable);
+
+        AllocatedResources {
+            cpu: allocated_amount(
+                saturating_sum(pods.iter().map(|pod| pod.cpu_requests)),
+                saturating_sum(pods.iter().map(|pod| pod.cpu_limits)),
+                cpu_allocatable),
+            memory: allocated_amount(
+                saturating_sum(pods.iter().map(|pod| pod.memory_requests)),
+                saturating_sum(pods.iter().map(|pod| pod.memory_limits)),
+                memory_allocatable),
+            pods,
+        }
+    }
+
+    // Quantities go up to i64::MAX, so a few huge limits stop at the maximum instead of overflowing
+    fn saturating_sum(amounts: impl Iterator<Item = i64>) -> i64 {
+        amounts.fold(0, i64::saturating_add)
+    }
+
+    fn allocated_amount(requests: i64, limits: i64, allocatable: i64) -> AllocatedAmount {
+
+        let percent = |amount: i64| (allocatable > 0).then(|| amount.saturating_mul(100) / allocatable);
+
+        AllocatedAmount {
+            requests,
+            requests_percent: percent(requests),
+            limits,
+            limits_percent: percent(limits),
+        }
+    }
+
+    // The way the scheduler sizes a pod: regular containers and sidecars run together, each plain init
+    // container runs alone next to the sidecars started before it, and the larger of the two counts.
+    // Pod overhead, e.g. from a sandboxed runtime, comes on top of requests and of limits that are set.
+    fn pod_amount(pod: &Pod, resource: Resource, limits: bool) -> i64 {
+
+        let container_amount = |container: &Container| if limits { resource.amount(&container.resources.limits) } else { resource.amount(&container.resources.requests) };
+
+        let containers = saturating_sum(pod.spec.containers.iter().map(container_amount));
+
+        let mut sidecars: i64 = 0;
+        let mut init_peak: i64 = 0;
+
+        for container in &pod.spec.init_containers {
+            let amount = container_amount(container);
+
+            if container.restart_policy.as_deref() == Some("Always") {
+                sidecars = sidecars.saturating_add(amount);
+                init_peak = init_peak.max(sidecars);
+            } else {
+                init_peak = init_peak.max(sidecars.saturating_add(amount));
+            }
+        }
+
+        let total = containers.saturating_add(sidecars).max(init_peak);
+
+        if limits && total == 0 {
+            total
+        } else {
+            total.saturating_add(resource.amount(&pod.spec.overhead))
+        }
+    }
+
+/**********************************************************************************************************/
+
+    #[cfg(test)]
+    mod tests {
+
+        use serde_json::json;
+
+        use super::*;
+
+        fn node(allocatable: serde_json::Value) -> Node {
+            serde_json::from_value(json!({
+                "metadata": { "name": "node-1" },
+                "status": { "allocatable": allocatable },
+            })).unwrap()
+        }
+
+        fn pod(name: &str, spec: serde_json::Value) -> Pod {
+            serde_json::from_value(json!({
+                "metadata": { "name": name, "namespace": "default" },
+                "spec": spec,
+                "status": { "phase": "Running" },
+            })).unwrap()
+        }
+
+        fn container(requests: serde_json::Value, limits: serde_json::Value) -> serde_json::Value {
+            json!({ "name": "c", "resources": { "requests": requests, "limits": limits } })
+        }
+
+        #[test]
+        fn regular_containers_add_up() {
+            let pod = pod("web", json!({
+                "containers": [
+                    container(json!({ "cpu": "100m", "memory": "64Mi" }), json!({ "cpu": "200m" })),
+                    container(json!({ "cpu": "250m", "memory": "64Mi" }), json!({ "cpu": "300m" })),
+                ],
+            }));
+
+            assert_eq!(pod_amount(&pod, Resource::Cpu, false), 350);
+            assert_eq!(pod_amount(&pod, Resource::Cpu, true), 500);
+            assert_eq!(pod_amount(&pod, Resource::Memory, false), 128 * 1024 * 1024);
+            assert_eq!(pod_amount(&pod, Resource::Memory, true), 0);
+        }
+
+        #[test]
+        fn init_container_counts_when_larger_than_the_regular_ones() {
+            let pod = pod("migrate", json!({
+                "initContainers": [container(json!({ "cpu": "2" }), json!({}))],
+                "containers": [container(json!({ "cpu": "500m" }), json!({}))],
+            }));
+
+            assert_eq!(pod_amount(&pod, Resource::Cpu, false), 2000);
+        }
+
+        #[test]
+        fn sidecars_run_next_to_later_init_containers_and_regular_containers() {
+            let sidecar = json!({ "name": "proxy", "restartPolicy": "Always", "resources": { "requests": { "cpu": "100m" } } });
+
+            // The init container after the sidecar runs next to it: 100m + 1, more than 100m + 500m
+            let pod_with_init_after = pod("after", json!({
+                "initContainers": [sidecar.clone(), container(json!({ "cpu": "1" }), json!({}))],
+                "containers": [container(json!({ "cpu": "500m" }), json!({}))],
+            }));
+            assert_eq!(pod_amount(&pod_with_init_after, Resource::Cpu, false), 1100);
+
+            // The init container before the sidecar runs alone
+            let pod_with_init_before = pod("before", json!({
+                "initContainers": [container(json!({ "cpu": "550m" }), json!({})), sidecar],
+                "containers": [container(json!({ "cpu": "500m" }), json!({}))],
+            }));
+            assert_eq!(pod_amount(&pod_with_init_before, Resource::Cpu, false), 600);
+        }
+
+        #[test]
+        fn overhead_is_added_to_requests_and_to_limits_that_are_set() {
+            let with_limits = pod("sandboxed", json!({
+                "containers": [container(json!({ "cpu": "100m" }), json!({ "cpu": "200m" }))],
+                "overhead": { "cpu": "250m" },
+            }));
+            assert_eq!(pod_amount(&with_limits, Resource::Cpu, false), 350);
+            assert_eq!(pod_amount(&with_limits, Resource::Cpu, true), 450);
+
+            let without_limits = pod("unbounded", json!({
+                "containers": [container(json!({ "cpu": "100m" }), json!({}))],
+                "overhead": { "cpu": "250m" },
+            }));
+            assert_eq!(pod_amount(&without_limits, Resource::Cpu, true), 0);
+        }
+
+        #[test]
+        fn totals_skip_terminated_pods_and_give_percentages_of_allocatable() {
+            let mut finished = pod("finished", json!({ "containers": [container(json!({ "cpu": "1" }), json!({}))] }));
+            finished.status.phase = Some(String::from("Succeeded"));
+
+            let pods = vec![
+                pod("b", json!({ "containers": [container(json!({ "cpu": "500m", "memory": "1Gi" }), json!({ "memory": "2Gi" }))] })),
+                pod("a", json!({ "containers": [container(json!({ "cpu": "1500m" }), json!({ "cpu": "4" }))] })),
+                finished,
+            ];
+
+            let allocated = process_allocated_resources(&node(json!({ "cpu": "4", "memory": "8Gi" })), &pods);
+
+            assert_eq!(allocated.pods.iter().map(|pod| pod.name.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+            assert_eq!(allocated.cpu.requests, 2000);
+            assert_eq!(allocated.cpu.requests_percent, Some(50));
+            assert_eq!(allocated.cpu.limits_percent, Some(100));
+            assert_eq!(allocated.memory.requests_percent, Some(12));
+            assert_eq!(allocated.memory.limits_percent, Some(25));
+        }
+
+        #[test]
+        fn percentages_are_null_without_allocatable() {
+            let pods = vec![pod("a", json!({ "containers": [container(json!({ "cpu": "1" }), json!({}))] }))];
+
+            let allocated = process_allocated_resources(&node(json!({})), &pods);
+
+            assert_eq!(allocated.cpu.requests, 1000);
+            assert_eq!(allocated.cpu.requests_percent, None);
+        }
+
+        #[test]
+        fn huge_limits_saturate_instead_of_overflowing() {
+            let huge = || pod("huge", json!({ "containers": [container(json!({}), json!({ "memory": "7Ei" }))] }));
+
+            let allocated = process_allocated_resources(&node(json!({ "memory": "8Gi" })), &[huge(), huge()]);
+
+            assert_eq!(allocated.memory.limits, i64::MAX);
+        }
+    }
+}
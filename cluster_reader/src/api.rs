@@ -2,42 +2,546 @@ pub mod api {
 
     use actix_web::web;
     use actix_web::{get, Responder, HttpResponse};
+    use serde::Deserialize;
 
-    use crate::use_case::use_case::{handle, get_namespaces, get_namespace_details_handler};
+    use crate::cluster_registry::cluster_registry::{ClusterHandle, ClusterRegistry, SelectedCluster};
+    use crate::error::error::ReaderError;
+    use crate::events::events::{change_stream, EventFilter};
+    use crate::kubeconfig::kubeconfig::Kubeconfig;
+    use crate::network::network::{get_network_details_handler, get_namespace_services_handler, get_namespace_ingresses_handler};
+    use crate::pods::pods::{get_pods_handler, get_namespace_pods_handler};
+    use crate::workloads::workloads::{get_workloads_handler, get_namespace_workloads_handler, get_workload_handler, workload_kind};
+    use crate::use_case::use_case::{handle, get_node_summaries_handler, get_node_handler, get_version_inventory_handler, get_namespaces, get_namespace_details_handler, get_namespace_deployments_handler, get_deployment_handler};
 
 
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    struct RefreshQuery {
+        // Skip the response cache and read the cluster again
+        #[serde(default)]
+        refresh: bool,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    struct ClusterInfoQuery {
+        #[serde(default)]
+        refresh: bool,
+        // Only nodes that accept new pods
+        #[serde(default)]
+        schedulable: bool,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    struct NodeQuery {
+        // Part of a name, address, pod CIDR or provider ID
+        search: Option<String>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    struct EventQuery {
+        // Comma separated kinds, e.g. nodes,deployments
+        resources: Option<String>,
+        namespace: Option<String>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    struct NamePath {
+        name: String,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    struct NamespacePath {
+        namespace: String,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    struct NamespacedNamePath {
+        namespace: String,
+        name: String,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    struct KindPath {
+        kind: String,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    struct NamespacedKindPath {
+        namespace: String,
+        kind: String,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    struct NamespacedKindNamePath {
+        namespace: String,
+        kind: String,
+        name: String,
+    }
+
+    #[get("/clusters")]
+    async fn get_clusters(registry: web::Data<ClusterRegistry>) -> impl Responder {
+        HttpResponse::Ok().json(registry.list())
+    }
+
+    #[get("/namespaces/{namespace}/deployments")]
+    async fn get_namespace_deployments(SelectedCluster(cluster): SelectedCluster, path: web::Path<NamespacePath>) -> Result<HttpResponse, ReaderError> {
+        let details = get_namespace_deployments_handler(cluster.source.as_ref(), &path.namespace).await?;
+
+        Ok(HttpResponse::Ok().json(details))
+    }
+
+    #[get("/namespaces/{namespace}/deployments/{name}")]
+    async fn get_deployment(SelectedCluster(cluster): SelectedCluster, path: web::Path<NamespacedNamePath>) -> Result<HttpResponse, ReaderError> {
+        let details = get_deployment_handler(cluster.source.as_ref(), &path.namespace, &path.name).await?;
+
+        Ok(HttpResponse::Ok().json(details))
+    }
+
+    #[get("/pods")]
+    async fn get_pods(SelectedCluster(cluster): SelectedCluster) -> Result<HttpResponse, ReaderError> {
+        let pods = get_pods_handler(cluster.source.as_ref()).await?;
+
+        Ok(HttpResponse::Ok().json(pods))
+    }
+
+    #[get("/namespaces/{namespace}/pods")]
+    async fn get_namespace_pods(SelectedCluster(cluster): SelectedCluster, path: web::Path<NamespacePath>) -> Result<HttpResponse, ReaderError> {
+        let pods = get_namespace_pods_handler(cluster.source.as_ref(), &path.namespace).await?;
+
+        Ok(HttpResponse::Ok().json(pods))
+    }
+
+    #[get("/workloads")]
+    async fn get_workloads(SelectedCluster(cluster): SelectedCluster) -> Result<HttpResponse, ReaderError> {
+        let workloads = get_workloads_handler(cluster.source.as_ref(), &[]).await?;
+
+        Ok(HttpResponse::Ok().json(workloads))
+    }
+
+    #[get("/workloads/{kind}")]
+    async fn get_workloads_of_kind(SelectedCluster(cluster): SelectedCluster, path: web::Path<KindPath>) -> Result<HttpResponse, ReaderError> {
+        let kind = workload_kind(&path.kind)?;
+        let workloads = get_workloads_handler(cluster.source.as_ref(), &[kind]).await?;
+
+        Ok(HttpResponse::Ok().json(workloads))
+    }
+
+    #[get("/namespaces/{namespace}/workloads")]
+    async fn get_namespace_workloads(SelectedCluster(cluster): SelectedCluster, path: web::Path<NamespacePath>) -> Result<HttpResponse, ReaderError> {
+        let workloads = get_namespace_workloads_handler(cluster.source.as_ref(), &path.namespace, &[]).await?;
+
+        Ok(HttpResponse::Ok().json(workloads))
+    }
+
+    #[get("/namespaces/{namespace}/workloads/{kind}")]
+    async fn get_namespace_workloads_of_kind(SelectedCluster(cluster): SelectedCluster, path: web::Path<NamespacedKindPath>) -> Result<HttpResponse, ReaderError> {
+        let kind = workload_kind(&path.kind)?;
+        let workloads = get_namespace_workloads_handler(cluster.source.as_ref(), &path.namespace, &[kind]).await?;
+
+        Ok(HttpResponse::Ok().json(workloads))
+    }
+
+    #[get("/namespaces/{namespace}/workloads/{kind}/{name}")]
+    async fn get_workload(SelectedCluster(cluster): SelectedCluster, path: web::Path<NamespacedKindNamePath>) -> Result<HttpResponse, ReaderError> {
+        let kind = workload_kind(&path.kind)?;
+        let workload = get_workload_handler(cluster.source.as_ref(), &path.namespace, kind, &path.name).await?;
+
+        Ok(HttpResponse::Ok().json(workload))
+    }
+
+    #[get("/namespaces/network-details")]
+    async fn get_network_details(SelectedCluster(cluster): SelectedCluster) -> Result<HttpResponse, ReaderError> {
+        let details = get_network_details_handler(cluster.source.as_ref()).await?;
+
+        Ok(HttpResponse::Ok().json(details))
+    }
+
+    #[get("/namespaces/{namespace}/services")]
+    async fn get_namespace_services(SelectedCluster(cluster): SelectedCluster, path: web::Path<NamespacePath>) -> Result<HttpResponse, ReaderError> {
+        let services = get_namespace_services_handler(cluster.source.as_ref(), &path.namespace).await?;
+
+        Ok(HttpResponse::Ok().json(services))
+    }
+
+    #[get("/namespaces/{namespace}/ingresses")]
+    async fn get_namespace_ingresses(SelectedCluster(cluster): SelectedCluster, path: web::Path<NamespacePath>) -> Result<HttpResponse, ReaderError> {
+        let ingresses = get_namespace_ingresses_handler(cluster.source.as_ref(), &path.namespace).await?;
+
+        Ok(HttpResponse::Ok().json(ingresses))
+    }
+
+    #[get("/kubeconfig")]
+    async fn get_kubeconfig(SelectedCluster(cluster): SelectedCluster) -> Result<HttpResponse, ReaderError> {
+        let kubeconfig = read_kubeconfig(&cluster)?;
+
+        Ok(HttpResponse::Ok().json(kubeconfig.summary()))
+    }
+
+    #[get("/kubeconfig/contexts")]
+    async fn get_kubeconfig_contexts(SelectedCluster(cluster): SelectedCluster) -> Result<HttpResponse, ReaderError> {
+        let kubeconfig = read_kubeconfig(&cluster)?;
+
+        Ok(HttpResponse::Ok().json(kubeconfig.summary().contexts()))
+    }
+
+    fn read_kubeconfig(cluster: &ClusterHandle) -> Result<Kubeconfig, ReaderError> {
+
+        let config_location = cluster.kubeconfig
+            .as_deref()
+            .ok_or_else(|| ReaderError::not_found(format!("Cluster {} is served from fixtures and has no kubeconfig", cluster.name)))?;
+
+        Kubeconfig::read(config_location)
+    }
+
     #[get("/cluster-info")]
-    async fn test_route() -> impl Responder {
-        
-        match handle().await {
-            Ok(cluster_values) => HttpResponse::Ok().json(cluster_values),
-            Err(err) => HttpResponse::InternalServerError().body(format!("Error: {}", err)),
+    async fn test_route(SelectedCluster(cluster): SelectedCluster, query: web::Query<ClusterInfoQuery>) -> Result<HttpResponse, ReaderError> {
+        let source = cluster.clone();
+        let cluster_values = cluster.cluster_info
+            .get(query.refresh, move || async move { handle(source.source.as_ref()).await })
+            .await?;
+
+        if query.schedulable {
+            return Ok(HttpResponse::Ok().json(cluster_values.schedulable()));
         }
+
+        Ok(HttpResponse::Ok().json(cluster_values.as_ref()))
+    }
+
+    #[get("/nodes")]
+    async fn get_nodes(SelectedCluster(cluster): SelectedCluster, query: web::Query<NodeQuery>) -> Result<HttpResponse, ReaderError> {
+        let nodes = get_node_summaries_handler(cluster.source.as_ref(), query.search.as_deref()).await?;
+
+        Ok(HttpResponse::Ok().json(nodes))
+    }
+
+    #[get("/nodes/{name}")]
+    async fn get_node(SelectedCluster(cluster): SelectedCluster, path: web::Path<NamePath>) -> Result<HttpResponse, ReaderError> {
+        let node = get_node_handler(cluster.source.as_ref(), &path.name).await?;
+
+        Ok(HttpResponse::Ok().json(node))
+    }
+
+    #[get("/versions")]
+    async fn get_versions(SelectedCluster(cluster): SelectedCluster) -> Result<HttpResponse, ReaderError> {
+        let versions = get_version_inventory_handler(cluster.source.as_ref()).await?;
+
+        Ok(HttpResponse::Ok().json(versions))
     }
 
     #[get("/namespaces")]
-    async fn get_namespaces_for_cluster() -> impl Responder {
-        
-        match get_namespaces().await {
-            Ok(namespaces) => HttpResponse::Ok().json(namespaces),
-            Err(err) => HttpResponse::InternalServerError().body(format!("Error: {}", err)),
-        }
+    async fn get_namespaces_for_cluster(SelectedCluster(cluster): SelectedCluster) -> Result<HttpResponse, ReaderError> {
+        let namespaces = get_namespaces(cluster.source.as_ref()).await?;
+
+        Ok(HttpResponse::Ok().json(namespaces))
     }
 
     #[get("namespaces/deployment-details")]
-    async fn get_deployment_details() -> impl Responder {
-        
-        match get_namespace_details_handler().await {
-            Ok(details) => HttpResponse::Ok().json(details),
-            Err(err) => HttpResponse::InternalServerError().body(format!("Error: {}", err)),
-        }
+    async fn get_deployment_details(SelectedCluster(cluster): SelectedCluster, query: web::Query<RefreshQuery>) -> Result<HttpResponse, ReaderError> {
+        let source = cluster.clone();
+        let details = cluster.deployment_details
+            .get(query.refresh, move || async move { get_namespace_details_handler(source.source.as_ref()).await })
+            .await?;
+
+        Ok(HttpResponse::Ok().json(details.as_ref()))
     }
 
-    pub fn scoped_config(cfg: &mut web::ServiceConfig) {
+    #[get("/events")]
+    async fn get_events(SelectedCluster(cluster): SelectedCluster, query: web::Query<EventQuery>) -> Result<HttpResponse, ReaderError> {
+        let query = query.into_inner();
+        let filter = EventFilter::new(query.resources.as_deref(), query.namespace)?;
+
+        let receiver = cluster.source
+            .subscribe()
+            .ok_or_else(|| ReaderError::not_found(format!("Cluster {} is not watched, changes are only streamed with watch enabled", cluster.name)))?;
+
+        Ok(HttpResponse::Ok()
+            .content_type("text/event-stream")
+            .insert_header(("Cache-Control", "no-cache"))
+            .streaming(change_stream(receiver, filter)))
+    }
+
+    // Routes that read a single cluster, served for the default cluster at the top level
+    // and for any registered cluster under /clusters/{cluster}
+    fn cluster_scoped_config(cfg: &mut web::ServiceConfig) {
         cfg
         .service(test_route)
+        .service(get_nodes)
+        .service(get_node)
+        .service(get_versions)
         .service(get_namespaces_for_cluster)
         .service(get_deployment_details)
+        .service(get_namespace_deployments)
+        .service(get_deployment)
+        .service(get_pods)
+        .service(get_namespace_pods)
+        .service(get_workloads)
+        .service(get_workloads_of_kind)
+        .service(get_namespace_workloads)
+        .service(get_namespace_workloads_of_kind)
+        .service(get_workload)
+        .service(get_network_details)
+        .service(get_namespace_services)
+        .service(get_namespace_ingresses)
+        .service(get_kubeconfig)
+        .service(get_kubeconfig_contexts)
+        .service(get_events)
         ;
     }
-}
\ No newline at end of file
+
+    pub fn scoped_config(cfg: &mut web::ServiceConfig) {
+        cfg
+        .service(get_clusters)
+        .service(web::scope("/clusters/{cluster}").configure(cluster_scoped_config))
+        .configure(cluster_scoped_config)
+        ;
+    }
+
+/**********************************************************************************************************/
+
+    #[cfg(test)]
+    mod tests {
+
+        use std::time::Duration;
+        use actix_web::{test, App};
+        use actix_web::http::StatusCode;
+        use serde_json::Value;
+
+        use crate::config::config::{ClusterConfig, Config, SourceKind};
+        use super::*;
+
+        // A small two node cluster, see the fixtures section of the README for the layout
+        const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/cluster");
+
+        fn cluster(name: &str) -> ClusterConfig {
+            ClusterConfig {
+                name: String::from(name),
+                kubeconfig: String::new(),
+                context: None,
+                fixtures: Some(String::from(FIXTURES)),
+            }
+        }
+
+        async fn get(path: &str) -> (StatusCode, Value) {
+
+            let config = Config {
+                bind_address: String::from("127.0.0.1"),
+                port: 0,
+                workers: 1,
+                json_limit: 4096,
+                cors_origins: Vec::new(),
+                max_concurrency: 4,
+                command_timeout: Duration::from_secs(5),
+                cache_ttl: Duration::from_secs(10),
+                watch: false,
+                resync_interval: Duration::from_secs(300),
+                kubectl_path: String::from("kubectl"),
+                source: SourceKind::Api,
+                default_cluster: String::from("dev"),
+                clusters: vec![cluster("dev"), cluster("prod")],
+            };
+
+            let registry = ClusterRegistry::from_config(&config).await.unwrap();
+            let app = test::init_service(App::new()
+                .app_data(web::Data::new(registry))
+                .service(web::scope("/api/v1").configure(scoped_config)))
+                .await;
+
+            let response = test::call_service(&app, test::TestRequest::get().uri(path).to_request()).await;
+            let status = response.status();
+
+            (status, test::read_body_json(response).await)
+        }
+
+        async fn get_ok(path: &str) -> Value {
+            let (status, body) = get(path).await;
+            assert_eq!(status, StatusCode::OK, "{}: {}", path, body);
+            body
+        }
+
+        async fn get_error(path: &str, expected: StatusCode) -> String {
+            let (status, body) = get(path).await;
+            assert_eq!(status, expected, "{}: {}", path, body);
+            String::from(body["code"].as_str().unwrap())
+        }
+
+        fn names(items: &Value, field: &str) -> Vec<String> {
+            items.as_array().unwrap()
+                .iter()
+                .map(|item| String::from(item[field].as_str().unwrap()))
+                .collect()
+        }
+
+        #[actix_web::test]
+        async fn lists_clusters() {
+            let clusters = get_ok("/api/v1/clusters").await;
+
+            assert_eq!(names(&clusters["clusters"], "name"), ["dev", "prod"]);
+            assert_eq!(clusters["clusters"][0]["default"], true);
+            assert_eq!(clusters["clusters"][1]["default"], false);
+        }
+
+        #[actix_web::test]
+        async fn cluster_info_summarizes_nodes() {
+            let info = get_ok("/api/v1/cluster-info").await;
+
+            assert_eq!(info["readiness"]["ready"], false);
+            assert_eq!(info["readiness"]["ready_nodes"], 2);
+            assert_eq!(info["readiness"]["problems"]["DiskPressure"][0], "worker-1");
+            assert_eq!(names(&info["nodes"], "name"), ["control-plane-1", "worker-1"]);
+
+            let schedulable = get_ok("/api/v1/cluster-info?schedulable=true").await;
+
+            assert_eq!(names(&schedulable["nodes"], "name"), ["worker-1"]);
+            assert_eq!(schedulable["readiness"]["total_nodes"], 2);
+        }
+
+        #[actix_web::test]
+        async fn nodes_carry_their_allocated_resources() {
+            let node = get_ok("/api/v1/nodes/worker-1").await;
+            let allocated = &node["allocated"];
+
+            // Two web pods with a sidecar and the db pod, the finished backup pod doesn't count
+            assert_eq!(allocated["cpu"]["requests"], 2 * (250 + 50) + 500);
+            assert_eq!(allocated["cpu"]["requests_percent"], 27);
+            assert_eq!(allocated["memory"]["requests"], 2 * (128 + 32) * 1024 * 1024 + 1024 * 1024 * 1024);
+            assert_eq!(allocated["memory"]["limits"], 2 * 256 * 1024 * 1024);
+            assert_eq!(names(&allocated["pods"], "name"), ["db-0", "web-5d8f7c9b6-r7mzt", "web-5d8f7c9b6-x2kqp"]);
+        }
+
+        #[actix_web::test]
+        async fn searches_and_gets_nodes() {
+            let nodes = get_ok("/api/v1/nodes?search=172.18.0.3").await;
+            assert_eq!(names(&nodes["nodes"], "name"), ["worker-1"]);
+
+            let nodes = get_ok("/api/v1/nodes?search=FD00:10:244").await;
+            assert_eq!(names(&nodes["nodes"], "name"), ["control-plane-1"]);
+
+            let node = get_ok("/api/v1/nodes/control-plane-1").await;
+            assert_eq!(node["roles"], "control-plane");
+            assert_eq!(node["schedulable"], false);
+
+            assert_eq!(get_error("/api/v1/nodes/worker-9", StatusCode::NOT_FOUND).await, "not_found");
+            assert_eq!(get_error("/api/v1/nodes/--help", StatusCode::BAD_REQUEST).await, "invalid_request");
+        }
+
+        #[actix_web::test]
+        async fn groups_nodes_by_version() {
+            let versions = get_ok("/api/v1/versions").await;
+
+            assert_eq!(versions["kubelet"]["v1.30.4"][0], "worker-1");
+            assert_eq!(versions["kubelet"]["v1.31.0"][0], "control-plane-1");
+            assert_eq!(versions["container_runtime"]["containerd://1.7.18"].as_array().unwrap().len(), 2);
+        }
+
+        #[actix_web::test]
+        async fn reads_namespaces_and_deployments() {
+            let namespaces = get_ok("/api/v1/namespaces").await;
+            assert_eq!(namespaces["namespaces"], serde_json::json!(["batch", "default", "kube-system"]));
+
+            let details = get_ok("/api/v1/namespaces/deployment-details").await;
+            assert_eq!(names(&details["total_details"], "namespace"), ["batch", "default", "kube-system"]);
+            assert_eq!(names(&details["total_details"][2]["deployment_details"], "deployment_name"), ["coredns"]);
+
+            let deployments = get_ok("/api/v1/namespaces/default/deployments").await;
+            assert_eq!(names(&deployments["deployment_details"], "deployment_name"), ["web"]);
+
+            let deployment = get_ok("/api/v1/namespaces/default/deployments/web").await;
+            assert_eq!(deployment["replicas"], 2);
+            assert_eq!(names(&deployment["containers"], "name"), ["log-shipper", "web"]);
+            assert_eq!(deployment["containers"][0]["sidecar"], true);
+            assert_eq!(names(&deployment["pods"], "name"), ["web-5d8f7c9b6-r7mzt", "web-5d8f7c9b6-x2kqp"]);
+
+            assert_eq!(get_error("/api/v1/namespaces/missing/deployments", StatusCode::NOT_FOUND).await, "not_found");
+            assert_eq!(get_error("/api/v1/namespaces/default/deployments/api", StatusCode::NOT_FOUND).await, "not_found");
+            assert_eq!(get_error("/api/v1/namespaces/default/deployments/Web", StatusCode::BAD_REQUEST).await, "invalid_request");
+        }
+
+        #[actix_web::test]
+        async fn lists_pods() {
+            let pods = get_ok("/api/v1/pods").await;
+            assert_eq!(pods["pods"].as_array().unwrap().len(), 5);
+
+            let pods = get_ok("/api/v1/namespaces/default/pods").await;
+            let db = &pods["pods"][0];
+
+            assert_eq!(db["name"], "db-0");
+            assert_eq!(db["owner"]["kind"], "StatefulSet");
+            assert_eq!(db["restart_count"], 4);
+            assert_eq!(db["containers"][0]["reason"], "CrashLoopBackOff");
+            assert_eq!(pods["pods"][1]["pod_ips"], serde_json::json!(["10.244.1.8", "fd00:10:244:1::8"]));
+
+            assert_eq!(get_error("/api/v1/namespaces/missing/pods", StatusCode::NOT_FOUND).await, "not_found");
+        }
+
+        #[actix_web::test]
+        async fn reads_workloads() {
+            let workloads = get_ok("/api/v1/workloads").await;
+            assert_eq!(workloads["workloads"].as_array().unwrap().len(), 7);
+
+            let stateful_sets = get_ok("/api/v1/workloads/statefulsets").await;
+            let db = &stateful_sets["workloads"][0];
+
+            assert_eq!(db["name"], "db");
+            assert_eq!(db["details"]["volume_claim_templates"][0]["storage"]["bytes"], 10_i64 * 1024 * 1024 * 1024);
+
+            let daemon_sets = get_ok("/api/v1/namespaces/kube-system/workloads/daemonsets").await;
+            assert_eq!(names(&daemon_sets["workloads"], "name"), ["kube-proxy"]);
+
+            // A cron job lists the pods of the jobs it is running, finished ones included
+            let backup = get_ok("/api/v1/namespaces/batch/workloads/cronjobs/backup").await;
+            assert_eq!(backup["details"]["active_jobs"], serde_json::json!(["backup-29345160"]));
+            assert_eq!(backup["pods"][0]["phase"], "Succeeded");
+
+            assert_eq!(get_error("/api/v1/workloads/widgets", StatusCode::BAD_REQUEST).await, "invalid_request");
+            assert_eq!(get_error("/api/v1/namespaces/batch/workloads/jobs/restore", StatusCode::NOT_FOUND).await, "not_found");
+        }
+
+        #[actix_web::test]
+        async fn reads_services_and_ingresses() {
+            let services = get_ok("/api/v1/namespaces/default/services").await;
+            let endpoints = &services["services"][0]["endpoints"];
+
+            // Both web pods are behind an IPv4 and an IPv6 slice
+            assert_eq!(endpoints["ready"], 2);
+            assert_eq!(endpoints["not_ready"], 0);
+            assert_eq!(endpoints["addresses"].as_array().unwrap().len(), 4);
+
+            let ingresses = get_ok("/api/v1/namespaces/default/ingresses").await;
+            let web = &ingresses["ingresses"][0];
+
+            assert_eq!(web["class"], "nginx");
+            assert_eq!(web["rules"][0]["paths"][0]["backend"]["service"], "web");
+            assert_eq!(web["tls"][0]["secret_name"], "web-tls");
+
+            let network = get_ok("/api/v1/namespaces/network-details").await;
+            assert_eq!(names(&network["total_details"], "namespace"), ["batch", "default", "kube-system"]);
+            assert_eq!(network["total_details"][1]["services"][0]["name"], "web");
+        }
+
+        #[actix_web::test]
+        async fn fixture_clusters_have_no_kubeconfig_or_events() {
+            assert_eq!(get_error("/api/v1/kubeconfig", StatusCode::NOT_FOUND).await, "not_found");
+            assert_eq!(get_error("/api/v1/events", StatusCode::NOT_FOUND).await, "not_found");
+        }
+
+        #[actix_web::test]
+        async fn routes_are_served_per_cluster() {
+            let namespaces = get_ok("/api/v1/clusters/prod/namespaces").await;
+            assert_eq!(namespaces["namespaces"][1], "default");
+
+            let node = get_ok("/api/v1/clusters/prod/nodes/worker-1").await;
+            assert_eq!(node["name"], "worker-1");
+
+            assert_eq!(get_error("/api/v1/clusters/staging/namespaces", StatusCode::NOT_FOUND).await, "not_found");
+        }
+    }
+}
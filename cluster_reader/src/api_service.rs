@@ -1,20 +1,22 @@
 pub mod api_service {
     
-    use std::io::Error;
+    use crate::error::error::ReaderError;
+    use crate::kubeconfig::kubeconfig::Kubeconfig;
 
-    pub fn check_config(environment_variable_key: &str, config_location: &str) -> Result<(), Error> {
+    pub fn check_config(cluster_name: &str, config_location: &str, context_name: Option<&str>) -> Result<(), ReaderError> {
 
         use std::fs;
-        use std::io::ErrorKind;
 
-        let directory_metadata = fs::metadata(&config_location)?;
+        let directory_metadata = fs::metadata(config_location)
+            .map_err(|err| ReaderError::kubeconfig_invalid(format!("Failed to read kubeconfig {}: {}", config_location, err)))?;
 
         if directory_metadata.is_dir() {
-            return Err(Error::new(ErrorKind::Unsupported, "Supplied config location is a directory"));        
+            Err(ReaderError::kubeconfig_invalid(format!("Supplied config location {} is a directory", config_location)))
         } else {
-            println!("Setting environtment varible of {} to {}", environment_variable_key, config_location);
-            std::env::set_var(environment_variable_key, config_location);
-            return Ok(());
+            Kubeconfig::read(config_location)?.validate(context_name)?;
+
+            println!("Using kubeconfig {} for cluster {}", config_location, cluster_name);
+            Ok(())
         }
     }
 }
\ No newline at end of file
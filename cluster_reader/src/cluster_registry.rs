@@ -0,0 +1,248 @@
+pub mod cluster_registry {
+
+    use std::collections::BTreeMap;
+    use std::future::{ready, Ready};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+    use serde::Serialize;
+    use tokio::sync::broadcast;
+    use tokio::sync::broadcast::error::RecvError;
+
+    use crate::api_service::api_service::check_config;
+    use crate::cluster_source::cluster_source::{ClusterSource, FixtureSource, KubectlSource};
+    use crate::config::config::{ClusterConfig, Config, SourceKind};
+    use crate::error::error::ReaderError;
+    use crate::informer::informer::{ClusterChange, InformerSource};
+    use crate::kube_client::kube_client::{KubeApiSource, KubeClient};
+    use crate::response_cache::response_cache::ResponseCache;
+    use crate::use_case::use_case::{ClusterValues, TotalDeploymentDetails};
+
+    pub struct ClusterHandle {
+        pub name: String,
+        pub context: Option<String>,
+        // None when the cluster is served from fixtures
+        pub kubeconfig: Option<String>,
+        pub source: Box<dyn ClusterSource>,
+        pub cluster_info: Arc<ResponseCache<ClusterValues>>,
+        pub deployment_details: Arc<ResponseCache<TotalDeploymentDetails>>,
+    }
+
+    impl ClusterHandle {
+
+        pub fn new(name: &str, context: Option<String>, kubeconfig: Option<String>, source: Box<dyn ClusterSource>, cache_ttl: Duration) -> ClusterHandle {
+
+            let handle = ClusterHandle {
+                name: String::from(name),
+                context,
+                kubeconfig,
+                cluster_info: Arc::new(ResponseCache::new(cache_ttl)),
+                deployment_details: Arc::new(ResponseCache::new(cache_ttl)),
+                source,
+            };
+
+            // A watched cluster keeps its cached responses until the model changes. Pods aren't part
+            // of the model, so they can still be up to the ttl old like on any other cluster.
+            if let Some(changes) = handle.source.subscribe() {
+                tokio::spawn(invalidate_on_change(changes, handle.cluster_info.clone(), handle.deployment_details.clone()));
+            }
+
+            handle
+        }
+    }
+
+    async fn invalidate_on_change(mut changes: broadcast::Receiver<ClusterChange>, cluster_info: Arc<ResponseCache<ClusterValues>>, deployment_details: Arc<ResponseCache<TotalDeploymentDetails>>) {
+        loop {
+            match changes.recv().await {
+                // Missed changes may have touched anything
+                Ok(_) | Err(RecvError::Lagged(_)) => {
+                    cluster_info.invalidate();
+                    deployment_details.invalidate();
+                }
+                Err(RecvError::Closed) => return,
+            }
+        }
+    }
+
+    pub struct ClusterRegistry {
+        clusters: BTreeMap<String, Arc<ClusterHandle>>,
+        default_cluster: String,
+    }
+
+    #[derive(Debug)]
+    #[derive(Serialize)]
+    pub struct ClusterSummary {
+        name: String,
+        context: Option<String>,
+        default: bool,
+    }
+
+    #[derive(Debug)]
+    #[derive(Serialize)]
+    pub struct Clusters {
+        clusters: Vec<ClusterSummary>,
+    }
+
+    impl ClusterRegistry {
+
+        pub async fn from_config(config: &Config) -> Result<ClusterRegistry, ReaderError> {
+
+            let mut clusters = BTreeMap::new();
+
+            for cluster in &config.clusters {
+                let source = build_source(config, cluster).await?;
+
+                let handle = ClusterHandle::new(
+                    &cluster.name,
+                    cluster.context.clone(),
+                    cluster.fixtures.is_none().then(|| cluster.kubeconfig.clone()),
+                    source,
+                    config.cache_ttl,
+                );
+
+                clusters.insert(cluster.name.clone(), Arc::new(handle));
+            }
+
+            Ok(ClusterRegistry {
+                clusters,
+                default_cluster: config.default_cluster.clone(),
+            })
+        }
+
+        pub fn get(&self, cluster_name: &str) -> Option<Arc<ClusterHandle>> {
+            self.clusters.get(cluster_name).cloned()
+        }
+
+        pub fn default_cluster(&self) -> Arc<ClusterHandle> {
+            self.clusters[&self.default_cluster].clone()
+        }
+
+        pub fn list(&self) -> Clusters {
+            Clusters {
+                clusters: self.clusters
+                    .values()
+                    .map(|cluster| ClusterSummary {
+                        name: cluster.name.clone(),
+                        context: cluster.context.clone(),
+                        default: cluster.name == self.default_cluster,
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    async fn build_source(config: &Config, cluster: &ClusterConfig) -> Result<Box<dyn ClusterSource>, ReaderError> {
+
+        // Serve canned kubectl output instead of a live cluster when a fixture directory is given
+        if let Some(fixture_location) = &cluster.fixtures {
+            println!("Serving cluster {} from fixtures in {}", cluster.name, fixture_location);
+            return Ok(Box::new(FixtureSource::from_dir(fixture_location)?));
+        }
+
+        check_config(&cluster.name, &cluster.kubeconfig, cluster.context.as_deref())?;
+
+        let source: Box<dyn ClusterSource> = match config.source {
+            SourceKind::Kubectl => Box::new(KubectlSource::new(&config.kubectl_path, &cluster.kubeconfig, cluster.context.as_deref(), config.max_concurrency, config.command_timeout)),
+            SourceKind::Api => Box::new(KubeApiSource::new(KubeClient::from_kubeconfig(&cluster.kubeconfig, cluster.context.as_deref(), config.max_concurrency, config.command_timeout).await?)),
+        };
+
+        if config.watch {
+            return Ok(Box::new(InformerSource::start(&cluster.name, source, config.resync_interval)));
+        }
+
+        Ok(source)
+    }
+
+/**********************************************************************************************************/
+
+    // The cluster a request is for, taken from the {cluster} route segment when there is one
+    // and the default cluster otherwise
+    pub struct SelectedCluster(pub Arc<ClusterHandle>);
+
+    impl FromRequest for SelectedCluster {
+        type Error = ReaderError;
+        type Future = Ready<Result<SelectedCluster, ReaderError>>;
+
+        fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+
+            let registry = match req.app_data::<web::Data<ClusterRegistry>>() {
+                Some(registry) => registry,
+                None => return ready(Err(ReaderError::internal("Cluster registry is not configured"))),
+            };
+
+            let selected = match req.match_info().get("cluster") {
+                Some(cluster_name) => registry
+                    .get(cluster_name)
+                    .ok_or_else(|| ReaderError::not_found(format!("Unknown cluster {}", cluster_name))),
+                None => Ok(registry.default_cluster()),
+            };
+
+            ready(selected.map(SelectedCluster))
+        }
+    }
+
+/**********************************************************************************************************/
+
+    #[cfg(test)]
+    mod tests {
+
+        use std::sync::Mutex;
+        use futures::channel::mpsc;
+        use futures::StreamExt;
+
+        use crate::cluster_source::cluster_source::ResourceKind;
+        use crate::informer::informer::tests::{event, object, ScriptedSource};
+        use crate::use_case::use_case::handle;
+        use super::*;
+
+        async fn cluster_info(cluster: &Arc<ClusterHandle>) -> Arc<ClusterValues> {
+            let source = cluster.clone();
+            cluster.cluster_info
+                .get(false, move || async move { handle(source.source.as_ref()).await })
+                .await
+                .unwrap()
+        }
+
+        fn take(calls: &Mutex<Vec<String>>) -> Vec<String> {
+            std::mem::take(&mut *calls.lock().unwrap())
+        }
+
+        #[tokio::test]
+        async fn watched_clusters_keep_responses_cached_until_the_model_changes() {
+            let (changes, node_events) = mpsc::unbounded();
+
+            let source = ScriptedSource::default()
+                .list(ResourceKind::Nodes, "5", vec![object("", "worker-1", "5"), object("", "worker-2", "5")])
+                .watch(ResourceKind::Nodes, node_events.boxed())
+                .list(ResourceKind::Namespaces, "5", Vec::new())
+                .list(ResourceKind::Deployments, "5", Vec::new());
+            let calls = source.calls();
+
+            let informer = InformerSource::start("test", Box::new(source), Duration::from_secs(300));
+            let cluster = Arc::new(ClusterHandle::new("test", None, None, Box::new(informer), Duration::from_secs(10)));
+
+            // Until the node list is in, nodes are read from the cluster
+            while cluster.source.list_nodes().await.unwrap() != ["worker-1", "worker-2"] {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+            take(&calls);
+
+            // Nodes come from the model, only their pods are listed and only on the first request
+            cluster_info(&cluster).await;
+            cluster_info(&cluster).await;
+            assert_eq!(take(&calls), ["list_node_pods worker-1", "list_node_pods worker-2"]);
+
+            changes.unbounded_send(event("MODIFIED", object("", "worker-2", "6"))).unwrap();
+
+            let mut attempts = 0;
+            while calls.lock().unwrap().is_empty() {
+                assert!(attempts < 1000, "the change did not clear the cache");
+                attempts += 1;
+
+                tokio::time::sleep(Duration::from_millis(1)).await;
+                cluster_info(&cluster).await;
+            }
+            assert_eq!(take(&calls), ["list_node_pods worker-1", "list_node_pods worker-2"]);
+        }
+    }
+}
@@ -0,0 +1,818 @@
+pub mod cluster_source {
+
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::fs;
+    use std::path::Path;
+    use std::process::{Output, Stdio};
+    use std::time::Duration;
+    use async_trait::async_trait;
+    use futures::stream::{self, BoxStream, StreamExt};
+    use serde::{Deserialize, Serialize};
+    use serde::de::DeserializeOwned;
+    use serde_json::Value;
+    use tokio::io::AsyncReadExt;
+    use tokio::process::{Child, Command};
+    use tokio::sync::{broadcast, Semaphore};
+
+    use crate::error::error::ReaderError;
+    use crate::informer::informer::ClusterChange;
+    use crate::kube_model::kube_model::{from_json, from_yaml, Deployment, EndpointSlice, Ingress, ItemList, Node, Pod, Service, Workload};
+
+    // Everything use_case needs from a cluster. Names are returned bare, without the
+    // "node/" or "namespace/" prefix kubectl adds with --output=name.
+    #[async_trait]
+    pub trait ClusterSource: Send + Sync {
+        async fn list_nodes(&self) -> Result<Vec<String>, ReaderError>;
+        async fn get_node(&self, node_name: &str) -> Result<Node, ReaderError>;
+        async fn list_namespaces(&self) -> Result<Vec<String>, ReaderError>;
+        async fn list_deployments(&self, namespace: &str) -> Result<Vec<String>, ReaderError>;
+        async fn get_deployment(&self, namespace: &str, deployment_name: &str) -> Result<Deployment, ReaderError>;
+        // Pods in one namespace, or in all of them when namespace is None
+        async fn list_pods(&self, namespace: Option<&str>) -> Result<Vec<Pod>, ReaderError>;
+        // Pods scheduled on one node, in every namespace
+        async fn list_node_pods(&self, node_name: &str) -> Result<Vec<Pod>, ReaderError>;
+        async fn list_services(&self, namespace: Option<&str>) -> Result<Vec<Service>, ReaderError>;
+        async fn list_endpoint_slices(&self, namespace: Option<&str>) -> Result<Vec<EndpointSlice>, ReaderError>;
+        async fn list_ingresses(&self, namespace: Option<&str>) -> Result<Vec<Ingress>, ReaderError>;
+        async fn list_workloads(&self, kind: WorkloadKind, namespace: Option<&str>) -> Result<Vec<Workload>, ReaderError>;
+
+        // Used by the informer to keep a live model of the cluster
+        async fn list_objects(&self, kind: ResourceKind) -> Result<ResourceList, ReaderError> {
+            Err(ReaderError::internal(format!("Listing {} is not supported by this source", kind.name())))
+        }
+
+        async fn watch_objects(&self, kind: ResourceKind, _resource_version: Option<&str>) -> Result<WatchStream, ReaderError> {
+            Err(ReaderError::internal(format!("Watching {} is not supported by this source", kind.name())))
+        }
+
+        // Changes to the cluster as they happen, only sources that keep a live model have them
+        fn subscribe(&self) -> Option<broadcast::Receiver<ClusterChange>> {
+            None
+        }
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone, Copy, PartialEq)]
+    pub enum ResourceKind {
+        Nodes,
+        Namespaces,
+        Deployments,
+    }
+
+    impl ResourceKind {
+
+        pub fn name(&self) -> &'static str {
+            match self {
+                ResourceKind::Nodes => "nodes",
+                ResourceKind::Namespaces => "namespaces",
+                ResourceKind::Deployments => "deployments",
+            }
+        }
+
+        pub fn from_name(name: &str) -> Option<ResourceKind> {
+            [ResourceKind::Nodes, ResourceKind::Namespaces, ResourceKind::Deployments]
+                .into_iter()
+                .find(|kind| kind.name() == name)
+        }
+
+        // Collection path across all namespaces
+        pub fn api_path(&self) -> &'static str {
+            match self {
+                ResourceKind::Nodes => "/api/v1/nodes",
+                ResourceKind::Namespaces => "/api/v1/namespaces",
+                ResourceKind::Deployments => "/apis/apps/v1/deployments",
+            }
+        }
+
+        pub fn namespaced(&self) -> bool {
+            *self == ResourceKind::Deployments
+        }
+    }
+
+    // The built-in kinds that run pods, listed for the /workloads routes
+    #[derive(Debug)]
+    #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum WorkloadKind {
+        Deployments,
+        StatefulSets,
+        DaemonSets,
+        ReplicaSets,
+        Jobs,
+        CronJobs,
+    }
+
+    impl WorkloadKind {
+
+        pub const ALL: [WorkloadKind; 6] = [
+            WorkloadKind::Deployments,
+            WorkloadKind::StatefulSets,
+            WorkloadKind::DaemonSets,
+            WorkloadKind::ReplicaSets,
+            WorkloadKind::Jobs,
+            WorkloadKind::CronJobs,
+        ];
+
+        pub fn name(&self) -> &'static str {
+            match self {
+                WorkloadKind::Deployments => "deployments",
+                WorkloadKind::StatefulSets => "statefulsets",
+                WorkloadKind::DaemonSets => "daemonsets",
+                WorkloadKind::ReplicaSets => "replicasets",
+                WorkloadKind::Jobs => "jobs",
+                WorkloadKind::CronJobs => "cronjobs",
+            }
+        }
+
+        pub fn from_name(name: &str) -> Option<WorkloadKind> {
+            WorkloadKind::ALL
+                .into_iter()
+                .find(|kind| kind.name() == name)
+        }
+
+        // The kind as it appears in the objects themselves
+        pub fn object_kind(&self) -> &'static str {
+            match self {
+                WorkloadKind::Deployments => "Deployment",
+                WorkloadKind::StatefulSets => "StatefulSet",
+                WorkloadKind::DaemonSets => "DaemonSet",
+                WorkloadKind::ReplicaSets => "ReplicaSet",
+                WorkloadKind::Jobs => "Job",
+                WorkloadKind::CronJobs => "CronJob",
+            }
+        }
+
+        pub fn group_version(&self) -> &'static str {
+            match self {
+                WorkloadKind::Jobs | WorkloadKind::CronJobs => "apis/batch/v1",
+                _ => "apis/apps/v1",
+            }
+        }
+
+        // Fully qualified so kubectl never picks an older group
+        pub fn kubectl_resource(&self) -> &'static str {
+            match self {
+                WorkloadKind::Deployments => "deployments.v1.apps",
+                WorkloadKind::StatefulSets => "statefulsets.v1.apps",
+                WorkloadKind::DaemonSets => "daemonsets.v1.apps",
+                WorkloadKind::ReplicaSets => "replicasets.v1.apps",
+                WorkloadKind::Jobs => "jobs.v1.batch",
+                WorkloadKind::CronJobs => "cronjobs.v1.batch",
+            }
+        }
+
+        pub fn parse(&self, item: Value) -> Result<Workload, ReaderError> {
+
+            let workload = match self {
+                WorkloadKind::Deployments => serde_json::from_value(item).map(Workload::Deployment),
+                WorkloadKind::StatefulSets => serde_json::from_value(item).map(Workload::StatefulSet),
+                WorkloadKind::DaemonSets => serde_json::from_value(item).map(Workload::DaemonSet),
+                WorkloadKind::ReplicaSets => serde_json::from_value(item).map(Workload::ReplicaSet),
+                WorkloadKind::Jobs => serde_json::from_value(item).map(Workload::Job),
+                WorkloadKind::CronJobs => serde_json::from_value(item).map(Workload::CronJob),
+            };
+
+            workload.map_err(|err| ReaderError::parse_failure(format!("Failed to parse {}: {}", self.name(), err)))
+        }
+    }
+
+    // A list response with the items left untyped, the informer parses them into the model
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ResourceList {
+        #[serde(default)]
+        pub api_version: String,
+        #[serde(default)]
+        pub kind: String,
+        #[serde(default)]
+        pub metadata: ListMeta,
+        #[serde(default)]
+        pub items: Vec<Value>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ListMeta {
+        // Empty when listed through kubectl, which does not report it
+        pub resource_version: Option<String>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone, Copy, PartialEq)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "UPPERCASE")]
+    pub enum WatchEventType {
+        Added,
+        Modified,
+        Deleted,
+        Bookmark,
+        Error,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    pub struct WatchEvent {
+        #[serde(rename = "type")]
+        pub event_type: WatchEventType,
+        pub object: Value,
+    }
+
+    pub type WatchStream = BoxStream<'static, Result<WatchEvent, ReaderError>>;
+
+/**********************************************************************************************************/
+
+    // Why an external command did not produce output
+    #[derive(Debug)]
+    #[derive(Serialize)]
+    #[serde(tag = "reason", rename_all = "snake_case")]
+    pub enum CommandError {
+        Timeout { command: String, timeout_seconds: u64 },
+        Failed { command: String, exit_code: Option<i32>, stderr: String },
+    }
+
+    impl fmt::Display for CommandError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                CommandError::Timeout { command, timeout_seconds } => write!(f, "{} did not finish within {}s and was killed", command, timeout_seconds),
+                CommandError::Failed { command, exit_code: Some(code), stderr } => write!(f, "{} exited with code {}: {}", command, code, stderr),
+                CommandError::Failed { command, exit_code: None, stderr } => write!(f, "{} was terminated by a signal: {}", command, stderr),
+            }
+        }
+    }
+
+    impl std::error::Error for CommandError {}
+
+    // Runs a command to completion, capturing stdout and stderr. The child is killed when it
+    // outlives the timeout or when the caller stops waiting, e.g. because the client disconnected.
+    pub async fn run_command(command: &mut Command, description: &str, timeout: Duration) -> Result<Output, ReaderError> {
+
+        let child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let output = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+            Ok(output) => output?,
+            Err(_) => return Err(CommandError::Timeout {
+                command: String::from(description),
+                timeout_seconds: timeout.as_secs(),
+            }.into()),
+        };
+
+        if !output.status.success() {
+            return Err(CommandError::Failed {
+                command: String::from(description),
+                exit_code: output.status.code(),
+                stderr: String::from(String::from_utf8_lossy(&output.stderr).trim()),
+            }.into());
+        }
+
+        Ok(output)
+    }
+
+/**********************************************************************************************************/
+
+    pub struct KubectlSource {
+        kubectl_path: String,
+        // Passed as --kubeconfig and --context so several clusters can share one process
+        kubeconfig: String,
+        context: Option<String>,
+        // Caps how many kubectl processes run at once for this cluster
+        permits: Semaphore,
+        timeout: Duration,
+    }
+
+    impl KubectlSource {
+
+        pub fn new(kubectl_path: &str, kubeconfig: &str, context: Option<&str>, max_concurrency: usize, timeout: Duration) -> KubectlSource {
+            KubectlSource {
+                kubectl_path: String::from(kubectl_path),
+                kubeconfig: String::from(kubeconfig),
+                context: context.map(String::from),
+                permits: Semaphore::new(max_concurrency),
+                timeout,
+            }
+        }
+
+        fn command(&self, args: &[&str]) -> Command {
+
+            let mut command = Command::new(&self.kubectl_path);
+            command.arg("--kubeconfig").arg(&self.kubeconfig);
+
+            if let Some(context) = &self.context {
+                command.arg("--context").arg(context);
+            }
+
+            command.args(args);
+
+            command
+        }
+
+        async fn run(&self, args: &[&str]) -> Result<String, ReaderError> {
+
+            let _permit = self.permits
+                .acquire()
+                .await
+                .map_err(|_| ReaderError::internal("kubectl concurrency limit closed"))?;
+
+            let mut command = self.command(args);
+
+            let output = run_command(&mut command, &format!("kubectl {}", args.join(" ")), self.timeout).await?;
+
+            String::from_utf8(output.stdout)
+                .map_err(|err| ReaderError::parse_failure(format!("kubectl {} wrote invalid UTF-8: {}", args.join(" "), err)))
+        }
+
+        async fn run_for_names(&self, args: &[&str]) -> Result<Vec<String>, ReaderError> {
+
+            let mut names = Vec::new();
+
+            // kubectl prints kind/name, anything else on stdout isn't a name
+            for line in self.run(args).await?.lines() {
+                if let Some((_, name)) = line.split_once('/') {
+                    names.push(String::from(name));
+                }
+            }
+
+            names.sort();
+
+            Ok(names)
+        }
+
+        async fn list_items<T: DeserializeOwned>(&self, resource: &str, namespace: Option<&str>) -> Result<Vec<T>, ReaderError> {
+
+            let list = match namespace {
+                Some(namespace) => self.run(&["get", resource, "-n", namespace, "--output=json"]).await?,
+                None => self.run(&["get", resource, "--all-namespaces", "--output=json"]).await?,
+            };
+
+            let list: ItemList<T> = from_json(&format!("{} list", resource), list.as_bytes())?;
+
+            Ok(list.items)
+        }
+    }
+
+    #[async_trait]
+    impl ClusterSource for KubectlSource {
+
+        async fn list_nodes(&self) -> Result<Vec<String>, ReaderError> {
+            self.run_for_names(&["get", "nodes", "--output=name"]).await
+        }
+
+        async fn get_node(&self, node_name: &str) -> Result<Node, ReaderError> {
+            // "--" ends the flags so a name can never be read as one
+            let node = self.run(&["get", "node", "--output=json", "--", node_name]).await?;
+
+            from_json(&format!("node {}", node_name), node.as_bytes())
+        }
+
+        async fn list_namespaces(&self) -> Result<Vec<String>, ReaderError> {
+            self.run_for_names(&["get", "namespaces", "--output=name"]).await
+        }
+
+        async fn list_deployments(&self, namespace: &str) -> Result<Vec<String>, ReaderError> {
+            self.run_for_names(&["get", "deployments", "-n", namespace, "--output=name"]).await
+        }
+
+        async fn get_deployment(&self, namespace: &str, deployment_name: &str) -> Result<Deployment, ReaderError> {
+            let deployment = self.run(&["get", "deployment", "-n", namespace, "-o", "yaml", "--", deployment_name]).await?;
+
+            from_yaml(&format!("deployment {}/{}", namespace, deployment_name), &deployment)
+        }
+
+        async fn list_pods(&self, namespace: Option<&str>) -> Result<Vec<Pod>, ReaderError> {
+            self.list_items("pods", namespace).await
+        }
+
+        async fn list_node_pods(&self, node_name: &str) -> Result<Vec<Pod>, ReaderError> {
+            let field_selector = format!("spec.nodeName={}", node_name);
+            let list = self.run(&["get", "pods", "--all-namespaces", "--field-selector", &field_selector, "--output=json"]).await?;
+
+            let list: ItemList<Pod> = from_json(&format!("pods on node {}", node_name), list.as_bytes())?;
+
+            Ok(list.items)
+        }
+
+        async fn list_services(&self, namespace: Option<&str>) -> Result<Vec<Service>, ReaderError> {
+            self.list_items("services", namespace).await
+        }
+
+        // Fully qualified so an older cluster's extensions group is never picked
+        async fn list_endpoint_slices(&self, namespace: Option<&str>) -> Result<Vec<EndpointSlice>, ReaderError> {
+            self.list_items("endpointslices.v1.discovery.k8s.io", namespace).await
+        }
+
+        async fn list_ingresses(&self, namespace: Option<&str>) -> Result<Vec<Ingress>, ReaderError> {
+            self.list_items("ingresses.v1.networking.k8s.io", namespace).await
+        }
+
+        async fn list_workloads(&self, kind: WorkloadKind, namespace: Option<&str>) -> Result<Vec<Workload>, ReaderError> {
+            self.list_items(kind.kubectl_resource(), namespace)
+                .await?
+                .into_iter()
+                .map(|item| kind.parse(item))
+                .collect()
+        }
+
+        async fn list_objects(&self, kind: ResourceKind) -> Result<ResourceList, ReaderError> {
+            let mut args = vec!["get", kind.name(), "--output=json"];
+            if kind.namespaced() {
+                args.push("--all-namespaces");
+            }
+
+            let list = self.run(&args).await?;
+
+            from_json(&format!("{} list", kind.name()), list.as_bytes())
+        }
+
+        // kubectl cannot resume from a resource version, so events between the list and the start of
+        // the watch are missed until the informer's next resync
+        async fn watch_objects(&self, kind: ResourceKind, _resource_version: Option<&str>) -> Result<WatchStream, ReaderError> {
+            let mut args = vec!["get", kind.name(), "--watch-only", "--output-watch-events", "--output=json"];
+            if kind.namespaced() {
+                args.push("--all-namespaces");
+            }
+
+            let child = self.command(&args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true)
+                .spawn()?;
+
+            Ok(kubectl_watch_events(child, format!("kubectl {}", args.join(" "))))
+        }
+    }
+
+    // kubectl prints each watch event as a pretty printed JSON object, so events are cut out of
+    // stdout with a streaming parser rather than by line
+    fn kubectl_watch_events(child: Child, description: String) -> WatchStream {
+
+        stream::unfold(Some((child, Vec::new())), move |state| {
+            let description = description.clone();
+
+            async move {
+                let (mut child, mut buffer) = state?;
+
+                loop {
+                    let mut events = serde_json::Deserializer::from_slice(&buffer).into_iter::<WatchEvent>();
+
+                    match events.next() {
+                        Some(Ok(event)) => {
+                            let consumed = events.byte_offset();
+                            buffer.drain(..consumed);
+                            return Some((Ok(event), Some((child, buffer))));
+                        }
+                        Some(Err(err)) if !err.is_eof() => {
+                            let err = ReaderError::parse_failure(format!("Failed to parse event from {}: {}", description, err));
+                            return Some((Err(err), None));
+                        }
+                        _ => {}
+                    }
+
+                    let mut chunk = [0u8; 8192];
+                    let read = match child.stdout.as_mut() {
+                        Some(stdout) => stdout.read(&mut chunk).await,
+                        None => Ok(0),
+                    };
+
+                    match read {
+                        Ok(0) => return watch_exit(child, &description).await.map(|err| (Err(err), None)),
+                        Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+                        Err(err) => return Some((Err(err.into()), None)),
+                    }
+                }
+            }
+        })
+        .boxed()
+    }
+
+    // A watch that exits cleanly just ends the stream, anything else is reported like a failed command
+    async fn watch_exit(mut child: Child, description: &str) -> Option<ReaderError> {
+
+        let mut stderr = String::new();
+        if let Some(mut pipe) = child.stderr.take() {
+            let _ = pipe.read_to_string(&mut stderr).await;
+        }
+
+        match child.wait().await {
+            Ok(status) if status.success() => None,
+            Ok(status) => Some(CommandError::Failed {
+                command: String::from(description),
+                exit_code: status.code(),
+                stderr: String::from(stderr.trim()),
+            }.into()),
+            Err(err) => Some(err.into()),
+        }
+    }
+
+/**********************************************************************************************************/
+
+    // Serves canned kubectl output from a directory laid out as:
+    //   nodes/<node>.json                                 output of `kubectl get node <node> -o json`
+    //   namespaces/<namespace>/                           one directory per namespace
+    //   namespaces/<namespace>/deployments/<name>.yaml    output of `kubectl get deployment <name> -o yaml`
+    //   namespaces/<namespace>/pods/<name>.yaml           output of `kubectl get pod <name> -o yaml`
+    //   namespaces/<namespace>/services/<name>.yaml       and likewise for services, endpointslices, ingresses,
+    //                                                     statefulsets, daemonsets, replicasets, jobs and cronjobs
+    pub struct FixtureSource {
+        nodes: HashMap<String, String>,
+        deployments: NamespacedFixtures,
+        pods: NamespacedFixtures,
+        services: NamespacedFixtures,
+        endpoint_slices: NamespacedFixtures,
+        ingresses: NamespacedFixtures,
+        // Every workload kind but deployments, which have their own field
+        workloads: HashMap<WorkloadKind, NamespacedFixtures>,
+    }
+
+    // Namespace to object name to file contents
+    type NamespacedFixtures = HashMap<String, HashMap<String, String>>;
+
+    impl FixtureSource {
+
+        pub fn from_dir(fixture_location: &str) -> Result<FixtureSource, ReaderError> {
+
+            let root = Path::new(fixture_location);
+
+            let nodes = read_files_by_stem(&root.join("nodes"), "json")?;
+
+            let mut deployments = HashMap::new();
+            let mut pods = HashMap::new();
+            let mut services = HashMap::new();
+            let mut endpoint_slices = HashMap::new();
+            let mut ingresses = HashMap::new();
+            let mut workloads: HashMap<WorkloadKind, NamespacedFixtures> = HashMap::new();
+
+            let namespaces_location = root.join("namespaces");
+            if namespaces_location.is_dir() {
+                for entry in fs::read_dir(&namespaces_location)? {
+                    let entry = entry?;
+                    if !entry.file_type()?.is_dir() {
+                        continue;
+                    }
+
+                    let namespace = entry.file_name().to_string_lossy().to_string();
+                    let namespace_deployments = read_files_by_stem(&entry.path().join("deployments"), "yaml")?;
+                    let namespace_pods = read_files_by_stem(&entry.path().join("pods"), "yaml")?;
+                    let namespace_services = read_files_by_stem(&entry.path().join("services"), "yaml")?;
+                    let namespace_endpoint_slices = read_files_by_stem(&entry.path().join("endpointslices"), "yaml")?;
+                    let namespace_ingresses = read_files_by_stem(&entry.path().join("ingresses"), "yaml")?;
+
+                    pods.insert(namespace.clone(), namespace_pods);
+                    services.insert(namespace.clone(), namespace_services);
+                    endpoint_slices.insert(namespace.clone(), namespace_endpoint_slices);
+                    ingresses.insert(namespace.clone(), namespace_ingresses);
+
+                    for kind in WorkloadKind::ALL.into_iter().filter(|kind| *kind != WorkloadKind::Deployments) {
+                        let namespace_workloads = read_files_by_stem(&entry.path().join(kind.name()), "yaml")?;
+                        workloads.entry(kind).or_default().insert(namespace.clone(), namespace_workloads);
+                    }
+                    deployments.insert(namespace, namespace_deployments);
+                }
+            }
+
+            Ok(FixtureSource {
+                nodes,
+                deployments,
+                pods,
+                services,
+                endpoint_slices,
+                ingresses,
+                workloads,
+            })
+        }
+
+        fn namespace_deployments(&self, namespace: &str) -> Result<&HashMap<String, String>, ReaderError> {
+            self.deployments
+                .get(namespace)
+                .ok_or_else(|| ReaderError::not_found(format!("No fixture for namespace {}", namespace)))
+        }
+    }
+
+    fn read_files_by_stem(directory: &Path, extension: &str) -> Result<HashMap<String, String>, ReaderError> {
+
+        let mut files = HashMap::new();
+
+        if !directory.is_dir() {
+            return Ok(files);
+        }
+
+        for entry in fs::read_dir(directory)? {
+            let path = entry?.path();
+
+            if path.extension().and_then(|value| value.to_str()) != Some(extension) {
+                continue;
+            }
+
+            if let Some(stem) = path.file_stem().and_then(|value| value.to_str()) {
+                files.insert(String::from(stem), fs::read_to_string(&path)?);
+            }
+        }
+
+        Ok(files)
+    }
+
+    fn sorted_keys(map: &HashMap<String, String>) -> Vec<String> {
+        let mut keys: Vec<String> = map.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    // Objects in one namespace, or in all of them when namespace is None
+    fn namespaced_objects<T: DeserializeOwned>(fixtures: &NamespacedFixtures, kind: &str, namespace: Option<&str>) -> Result<Vec<T>, ReaderError> {
+
+        let mut objects = Vec::new();
+
+        for (object_namespace, namespace_objects) in fixtures {
+            if namespace.is_some_and(|namespace| namespace != object_namespace) {
+                continue;
+            }
+
+            for name in sorted_keys(namespace_objects) {
+                let what = format!("{} {}/{}", kind, object_namespace, name);
+                let mut object: Value = from_yaml(&what, &namespace_objects[&name])?;
+
+                // Fixtures may leave the namespace out, the directory they are in tells
+                if let Some(metadata) = object.get_mut("metadata").and_then(Value::as_object_mut) {
+                    metadata.entry("namespace").or_insert_with(|| Value::from(object_namespace.as_str()));
+                }
+
+                objects.push(serde_json::from_value(object)
+                    .map_err(|err| ReaderError::parse_failure(format!("Failed to parse {}: {}", what, err)))?);
+            }
+        }
+
+        Ok(objects)
+    }
+
+    #[async_trait]
+    impl ClusterSource for FixtureSource {
+
+        async fn list_nodes(&self) -> Result<Vec<String>, ReaderError> {
+            Ok(sorted_keys(&self.nodes))
+        }
+
+        async fn get_node(&self, node_name: &str) -> Result<Node, ReaderError> {
+            let node = self.nodes
+                .get(node_name)
+                .ok_or_else(|| ReaderError::not_found(format!("No fixture for node {}", node_name)))?;
+
+            from_json(&format!("node {}", node_name), node.as_bytes())
+        }
+
+        async fn list_namespaces(&self) -> Result<Vec<String>, ReaderError> {
+            let mut namespaces: Vec<String> = self.deployments.keys().cloned().collect();
+            namespaces.sort();
+            Ok(namespaces)
+        }
+
+        async fn list_deployments(&self, namespace: &str) -> Result<Vec<String>, ReaderError> {
+            Ok(sorted_keys(self.namespace_deployments(namespace)?))
+        }
+
+        async fn get_deployment(&self, namespace: &str, deployment_name: &str) -> Result<Deployment, ReaderError> {
+            let deployment = self.namespace_deployments(namespace)?
+                .get(deployment_name)
+                .ok_or_else(|| ReaderError::not_found(format!("No fixture for deployment {}/{}", namespace, deployment_name)))?;
+
+            from_yaml(&format!("deployment {}/{}", namespace, deployment_name), deployment)
+        }
+
+        async fn list_pods(&self, namespace: Option<&str>) -> Result<Vec<Pod>, ReaderError> {
+            namespaced_objects(&self.pods, "pod", namespace)
+        }
+
+        async fn list_node_pods(&self, node_name: &str) -> Result<Vec<Pod>, ReaderError> {
+            let pods: Vec<Pod> = namespaced_objects(&self.pods, "pod", None)?;
+
+            Ok(pods
+                .into_iter()
+                .filter(|pod| pod.spec.node_name.as_deref() == Some(node_name))
+                .collect())
+        }
+
+        async fn list_services(&self, namespace: Option<&str>) -> Result<Vec<Service>, ReaderError> {
+            namespaced_objects(&self.services, "service", namespace)
+        }
+
+        async fn list_endpoint_slices(&self, namespace: Option<&str>) -> Result<Vec<EndpointSlice>, ReaderError> {
+            namespaced_objects(&self.endpoint_slices, "endpointslice", namespace)
+        }
+
+        async fn list_ingresses(&self, namespace: Option<&str>) -> Result<Vec<Ingress>, ReaderError> {
+            namespaced_objects(&self.ingresses, "ingress", namespace)
+        }
+
+        async fn list_workloads(&self, kind: WorkloadKind, namespace: Option<&str>) -> Result<Vec<Workload>, ReaderError> {
+            let empty = NamespacedFixtures::new();
+            let fixtures = match kind {
+                WorkloadKind::Deployments => &self.deployments,
+                _ => self.workloads.get(&kind).unwrap_or(&empty),
+            };
+
+            namespaced_objects::<Value>(fixtures, kind.object_kind(), namespace)?
+                .into_iter()
+                .map(|item| kind.parse(item))
+                .collect()
+        }
+    }
+
+/**********************************************************************************************************/
+
+    #[cfg(test)]
+    mod tests {
+
+        use std::time::Instant;
+        use serde_json::json;
+
+        use super::*;
+
+        async fn run(script: &str, timeout: Duration) -> Result<Output, ReaderError> {
+            run_command(Command::new("sh").args(["-c", script]), "kubectl get nodes", timeout).await
+        }
+
+        async fn failure(script: &str) -> ReaderError {
+            run(script, Duration::from_secs(5)).await.err().unwrap()
+        }
+
+        #[tokio::test]
+        async fn returns_the_output_of_a_successful_command() {
+            let output = run("echo node/worker-1; echo ignored >&2", Duration::from_secs(5)).await.unwrap();
+
+            assert_eq!(output.stdout, b"node/worker-1\n");
+        }
+
+        #[tokio::test]
+        async fn kills_commands_that_outlive_the_timeout() {
+            let marker = std::env::temp_dir().join(format!("cluster_reader_run_command_{}", std::process::id()));
+            let script = format!("sleep 2 && touch {}", marker.display());
+
+            let started = Instant::now();
+            let err = run(&script, Duration::from_secs(1)).await.err().unwrap();
+
+            assert!(started.elapsed() < Duration::from_millis(1500), "took {:?}", started.elapsed());
+            assert!(matches!(err, ReaderError::Timeout { .. }), "{:?}", err);
+            assert_eq!(err.message(), "kubectl get nodes did not finish within 1s and was killed");
+            assert_eq!(err.details(), Some(&json!({ "reason": "timeout", "command": "kubectl get nodes", "timeout_seconds": 1 })));
+
+            // Had it not been killed it would have created the marker by now
+            tokio::time::sleep(Duration::from_millis(1500)).await;
+            assert!(!marker.exists());
+        }
+
+        #[tokio::test]
+        async fn captures_the_exit_code_and_stderr() {
+            let err = failure("echo partial output; echo '  Error from server (Forbidden): nodes is forbidden  ' >&2; exit 1").await;
+
+            assert_eq!(err.message(), "kubectl get nodes exited with code 1: Error from server (Forbidden): nodes is forbidden");
+            assert_eq!(err.details(), Some(&json!({
+                "reason": "failed",
+                "command": "kubectl get nodes",
+                "exit_code": 1,
+                "stderr": "Error from server (Forbidden): nodes is forbidden",
+            })));
+
+            let err = failure("echo 'error: unknown flag: --bogus' >&2; exit 3").await;
+            assert_eq!(err.details().unwrap()["exit_code"], 3);
+
+            let err = failure("kill -9 $$").await;
+            assert_eq!(err.details().unwrap()["exit_code"], json!(null));
+            assert!(err.message().starts_with("kubectl get nodes was terminated by a signal"), "{}", err.message());
+        }
+
+        #[tokio::test]
+        async fn sorts_failures_by_their_stderr() {
+            let cases = [
+                ("Error from server (Forbidden): nodes is forbidden: User \"reader\" cannot list resource \"nodes\"", "forbidden"),
+                ("error: You must be logged in to the server (Unauthorized)", "forbidden"),
+                ("Error from server (NotFound): deployments.apps \"web\" not found", "not_found"),
+                ("Error from server (NotFound): namespaces \"apps\" not found", "not_found"),
+                ("Unable to connect to the server: dial tcp 10.0.0.1:6443: connect: connection refused", "cluster_unreachable"),
+                ("Unable to connect to the server: dial tcp: lookup api.example.com: no such host", "cluster_unreachable"),
+                ("Unable to connect to the server: dial tcp 10.0.0.1:6443: i/o timeout", "cluster_unreachable"),
+                ("error: the server doesn't have a resource type \"widgets\"", "upstream_failure"),
+                ("", "upstream_failure"),
+            ];
+
+            for (stderr, code) in cases {
+                let err = failure(&format!("printf '%s\\n' '{}' >&2; exit 1", stderr.replace('\'', "'\\''"))).await;
+
+                assert_eq!(err.code(), code, "{}", stderr);
+                assert_eq!(err.details().unwrap()["stderr"], stderr);
+            }
+        }
+
+        #[tokio::test]
+        async fn reports_commands_that_cannot_start() {
+            let err = run_command(&mut Command::new("/nonexistent/kubectl"), "kubectl get nodes", Duration::from_secs(5)).await.err().unwrap();
+
+            assert!(matches!(err, ReaderError::Internal { .. }), "{:?}", err);
+        }
+    }
+}
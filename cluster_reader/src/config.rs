@@ -0,0 +1,578 @@
+pub mod config {
+
+    use std::fmt;
+    use std::fs;
+    use std::net::ToSocketAddrs;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+    use std::ffi::OsString;
+    use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
+    use serde::Deserialize;
+
+    const DEFAULT_CLUSTER_NAME: &str = "default";
+
+    #[derive(Debug)]
+    #[derive(Clone, Copy, PartialEq)]
+    #[derive(Deserialize)]
+    #[derive(ValueEnum)]
+    #[serde(rename_all = "lowercase")]
+    pub enum SourceKind {
+        Api,
+        Kubectl,
+    }
+
+    // Command line flags, each one can also be given as an environment variable.
+    // Anything left unset falls back to the config file and then to the defaults.
+    #[derive(Debug)]
+    #[derive(Parser)]
+    #[command(version, about = "Serves cluster info as JSON")]
+    struct CommandLine {
+        /// TOML file with any of the settings below
+        #[arg(long, env = "CLUSTER_READER_CONFIG")]
+        config: Option<PathBuf>,
+
+        /// Kubeconfig used to reach the cluster
+        #[arg(long, env = "CLUSTER_READER_KUBECONFIG")]
+        kubeconfig: Option<String>,
+
+        #[arg(long, env = "CLUSTER_READER_BIND_ADDRESS")]
+        bind_address: Option<String>,
+
+        #[arg(long, env = "CLUSTER_READER_PORT")]
+        port: Option<u16>,
+
+        #[arg(long, env = "CLUSTER_READER_WORKERS")]
+        workers: Option<usize>,
+
+        /// Maximum JSON request body size in bytes
+        #[arg(long, env = "CLUSTER_READER_JSON_LIMIT")]
+        json_limit: Option<usize>,
+
+        /// Allowed CORS origins, "*" allows any origin
+        #[arg(long = "cors-origin", env = "CLUSTER_READER_CORS_ORIGINS", value_delimiter = ',')]
+        cors_origins: Option<Vec<String>>,
+
+        /// Most kubectl processes or apiserver requests running at once per cluster
+        #[arg(long, env = "CLUSTER_READER_MAX_CONCURRENCY")]
+        max_concurrency: Option<usize>,
+
+        /// Seconds a kubectl process or apiserver request may take before it is abandoned
+        #[arg(long, env = "CLUSTER_READER_COMMAND_TIMEOUT")]
+        command_timeout: Option<u64>,
+
+        /// Seconds a computed /cluster-info or deployment-details response is reused, 0 disables caching
+        #[arg(long, env = "CLUSTER_READER_CACHE_TTL")]
+        cache_ttl: Option<u64>,
+
+        /// Keep a live model of the cluster from list and watch instead of reading it per request
+        #[arg(long, env = "CLUSTER_READER_WATCH", num_args = 0..=1, default_missing_value = "true")]
+        watch: Option<bool>,
+
+        /// Seconds between full relists of the watched model
+        #[arg(long, env = "CLUSTER_READER_RESYNC_INTERVAL")]
+        resync_interval: Option<u64>,
+
+        #[arg(long, env = "CLUSTER_READER_KUBECTL_PATH")]
+        kubectl_path: Option<String>,
+
+        /// Talk to the apiserver directly or shell out to kubectl
+        #[arg(long, env = "CLUSTER_READER_SOURCE")]
+        source: Option<SourceKind>,
+
+        /// Serve canned kubectl output from this directory instead of a live cluster
+        #[arg(long, env = "CLUSTER_READER_FIXTURES")]
+        fixtures: Option<String>,
+
+        /// Cluster served by the routes without a /clusters/{cluster} prefix
+        #[arg(long, env = "CLUSTER_READER_DEFAULT_CLUSTER")]
+        default_cluster: Option<String>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct FileClusterConfig {
+        name: String,
+        kubeconfig: Option<String>,
+        context: Option<String>,
+        fixtures: Option<String>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct FileConfig {
+        kubeconfig: Option<String>,
+        bind_address: Option<String>,
+        port: Option<u16>,
+        workers: Option<usize>,
+        json_limit: Option<usize>,
+        cors_origins: Option<Vec<String>>,
+        max_concurrency: Option<usize>,
+        command_timeout: Option<u64>,
+        cache_ttl: Option<u64>,
+        watch: Option<bool>,
+        resync_interval: Option<u64>,
+        kubectl_path: Option<String>,
+        source: Option<SourceKind>,
+        fixtures: Option<String>,
+        default_cluster: Option<String>,
+        #[serde(default)]
+        clusters: Vec<FileClusterConfig>,
+    }
+
+    // One entry of the cluster registry, kubeconfig and fixtures fall back to the top level settings
+    #[derive(Debug)]
+    pub struct ClusterConfig {
+        pub name: String,
+        pub kubeconfig: String,
+        pub context: Option<String>,
+        pub fixtures: Option<String>,
+    }
+
+    #[derive(Debug)]
+    pub struct Config {
+        pub bind_address: String,
+        pub port: u16,
+        pub workers: usize,
+        pub json_limit: usize,
+        pub cors_origins: Vec<String>,
+        pub max_concurrency: usize,
+        pub command_timeout: Duration,
+        pub cache_ttl: Duration,
+        pub watch: bool,
+        pub resync_interval: Duration,
+        pub kubectl_path: String,
+        pub source: SourceKind,
+        pub default_cluster: String,
+        pub clusters: Vec<ClusterConfig>,
+    }
+
+    #[derive(Debug)]
+    pub struct ConfigError(String);
+
+    impl fmt::Display for ConfigError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl std::error::Error for ConfigError {}
+
+    impl CommandLine {
+
+        // Parses the flags, taking any flag that isn't given from its environment variable in `env`
+        fn parse_with_env<I, T>(args: I, env: &dyn Fn(&str) -> Option<String>) -> Result<CommandLine, clap::Error>
+        where
+            I: IntoIterator<Item = T>,
+            T: Into<OsString> + Clone,
+        {
+            let command = CommandLine::command().mut_args(|arg| {
+                let value = arg.get_env().and_then(|name| env(&name.to_string_lossy()));
+
+                match value {
+                    Some(value) => arg.env(None).default_value(value),
+                    None => arg.env(None),
+                }
+            });
+
+            CommandLine::from_arg_matches(&command.try_get_matches_from(args)?)
+        }
+    }
+
+    impl Config {
+
+        pub fn load() -> Result<Config, ConfigError> {
+            Config::load_from(std::env::args_os(), &|name| std::env::var(name).ok())
+        }
+
+        // Layers the config file, `env` and the command line `args` the way load does with the real ones
+        pub fn load_from<I, T>(args: I, env: &dyn Fn(&str) -> Option<String>) -> Result<Config, ConfigError>
+        where
+            I: IntoIterator<Item = T>,
+            T: Into<OsString> + Clone,
+        {
+            let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+
+            let command_line = match CommandLine::parse_with_env(args.clone(), env) {
+                Ok(command_line) => command_line,
+                // --help and --version print and exit, the unchanged command lists the environment variables in its help
+                Err(err) if !err.use_stderr() => {
+                    CommandLine::command().get_matches_from(args);
+                    err.exit()
+                }
+                Err(err) => return Err(ConfigError(String::from(err.to_string().trim_end()))),
+            };
+
+            let file = match &command_line.config {
+                Some(config_location) => read_config_file(config_location)?,
+                None => FileConfig::default(),
+            };
+
+            let kubeconfig = command_line.kubeconfig
+                .or(file.kubeconfig)
+                .unwrap_or_else(|| default_kubeconfig(env));
+            let fixtures = command_line.fixtures.or(file.fixtures);
+
+            // Without a clusters list the reader serves a single cluster called "default"
+            let clusters: Vec<ClusterConfig> = if file.clusters.is_empty() {
+                vec![ClusterConfig {
+                    name: String::from(DEFAULT_CLUSTER_NAME),
+                    kubeconfig,
+                    context: None,
+                    fixtures,
+                }]
+            } else {
+                file.clusters
+                    .into_iter()
+                    .map(|cluster| ClusterConfig {
+                        name: cluster.name,
+                        kubeconfig: cluster.kubeconfig.unwrap_or_else(|| kubeconfig.clone()),
+                        context: cluster.context,
+                        fixtures: cluster.fixtures.or_else(|| fixtures.clone()),
+                    })
+                    .collect()
+            };
+
+            let default_cluster = command_line.default_cluster
+                .or(file.default_cluster)
+                .unwrap_or_else(|| clusters[0].name.clone());
+
+            let config = Config {
+                bind_address: command_line.bind_address
+                    .or(file.bind_address)
+                    .unwrap_or_else(|| String::from("127.0.0.1")),
+                port: command_line.port.or(file.port).unwrap_or(8080),
+                workers: command_line.workers.or(file.workers).unwrap_or(2),
+                json_limit: command_line.json_limit.or(file.json_limit).unwrap_or(4096),
+                cors_origins: command_line.cors_origins
+                    .or(file.cors_origins)
+                    .unwrap_or_else(|| vec![String::from("*")]),
+                max_concurrency: command_line.max_concurrency.or(file.max_concurrency).unwrap_or(8),
+                command_timeout: Duration::from_secs(command_line.command_timeout.or(file.command_timeout).unwrap_or(30)),
+                cache_ttl: Duration::from_secs(command_line.cache_ttl.or(file.cache_ttl).unwrap_or(10)),
+                watch: command_line.watch.or(file.watch).unwrap_or(true),
+                resync_interval: Duration::from_secs(command_line.resync_interval.or(file.resync_interval).unwrap_or(300)),
+                kubectl_path: command_line.kubectl_path
+                    .or(file.kubectl_path)
+                    .unwrap_or_else(|| String::from("kubectl")),
+                source: command_line.source.or(file.source).unwrap_or(SourceKind::Api),
+                default_cluster,
+                clusters,
+            };
+
+            config.validate(env)?;
+
+            Ok(config)
+        }
+
+        fn validate(&self, env: &dyn Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+
+            if self.workers == 0 {
+                return Err(ConfigError(String::from("workers must be at least 1")));
+            }
+
+            if self.max_concurrency == 0 {
+                return Err(ConfigError(String::from("max_concurrency must be at least 1")));
+            }
+
+            if self.command_timeout.is_zero() {
+                return Err(ConfigError(String::from("command_timeout must be at least 1 second")));
+            }
+
+            if self.resync_interval.is_zero() {
+                return Err(ConfigError(String::from("resync_interval must be at least 1 second")));
+            }
+
+            if self.json_limit == 0 {
+                return Err(ConfigError(String::from("json_limit must be at least 1 byte")));
+            }
+
+            if let Err(err) = (self.bind_address.as_str(), self.port).to_socket_addrs() {
+                return Err(ConfigError(format!("bind_address {}:{} is not a usable address: {}", self.bind_address, self.port, err)));
+            }
+
+            for origin in &self.cors_origins {
+                if origin != "*" && !origin.starts_with("http://") && !origin.starts_with("https://") {
+                    return Err(ConfigError(format!("cors origin {} must be \"*\" or start with http:// or https://", origin)));
+                }
+            }
+
+            if !self.clusters.iter().any(|cluster| cluster.name == self.default_cluster) {
+                return Err(ConfigError(format!("default_cluster {} is not one of the configured clusters", self.default_cluster)));
+            }
+
+            for (index, cluster) in self.clusters.iter().enumerate() {
+                self.validate_cluster(cluster, env)?;
+
+                if self.clusters[..index].iter().any(|other| other.name == cluster.name) {
+                    return Err(ConfigError(format!("cluster {} is configured more than once", cluster.name)));
+                }
+            }
+
+            Ok(())
+        }
+
+        fn validate_cluster(&self, cluster: &ClusterConfig, env: &dyn Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+
+            let valid_name = !cluster.name.is_empty() && cluster.name
+                .chars()
+                .all(|character| character.is_ascii_alphanumeric() || character == '-' || character == '_' || character == '.');
+
+            if !valid_name {
+                return Err(ConfigError(format!("cluster name \"{}\" may only contain letters, digits, '-', '_' and '.'", cluster.name)));
+            }
+
+            if let Some(fixtures) = &cluster.fixtures {
+                if !Path::new(fixtures).is_dir() {
+                    return Err(ConfigError(format!("fixtures location {} for cluster {} is not a directory", fixtures, cluster.name)));
+                }
+                return Ok(());
+            }
+
+            if !Path::new(&cluster.kubeconfig).is_file() {
+                return Err(ConfigError(format!("kubeconfig {} for cluster {} does not exist or is not a file", cluster.kubeconfig, cluster.name)));
+            }
+
+            if self.source == SourceKind::Kubectl && find_executable(&self.kubectl_path, env).is_none() {
+                return Err(ConfigError(format!("kubectl_path {} was not found or is not a file", self.kubectl_path)));
+            }
+
+            Ok(())
+        }
+    }
+
+    fn read_config_file(config_location: &Path) -> Result<FileConfig, ConfigError> {
+
+        let contents = fs::read_to_string(config_location)
+            .map_err(|err| ConfigError(format!("Failed to read config file {}: {}", config_location.display(), err)))?;
+
+        toml::from_str(&contents)
+            .map_err(|err| ConfigError(format!("Invalid config file {}: {}", config_location.display(), err)))
+    }
+
+    // Same lookup kubectl does, first entry of $KUBECONFIG and then ~/.kube/config
+    fn default_kubeconfig(env: &dyn Fn(&str) -> Option<String>) -> String {
+
+        if let Some(kubeconfig) = env("KUBECONFIG") {
+            if let Some(first) = std::env::split_paths(&kubeconfig).next() {
+                return first.to_string_lossy().to_string();
+            }
+        }
+
+        let home = env("HOME").unwrap_or_default();
+
+        Path::new(&home).join(".kube").join("config").to_string_lossy().to_string()
+    }
+
+    fn find_executable(program: &str, env: &dyn Fn(&str) -> Option<String>) -> Option<PathBuf> {
+
+        let program_path = Path::new(program);
+
+        if program_path.components().count() > 1 {
+            return program_path.is_file().then(|| program_path.to_path_buf());
+        }
+
+        env("PATH")
+            .map(|paths| std::env::split_paths(&paths)
+                .map(|directory| directory.join(program))
+                .find(|candidate| candidate.is_file()))
+            .unwrap_or_default()
+    }
+
+/**********************************************************************************************************/
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::*;
+
+        const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/cluster");
+
+        fn load(args: &[&str], vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+            let env = |name: &str| vars
+                .iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| String::from(*value));
+
+            Config::load_from(std::iter::once("cluster_reader").chain(args.iter().copied()), &env)
+        }
+
+        fn error(args: &[&str], vars: &[(&str, &str)]) -> String {
+            match load(args, vars) {
+                Ok(config) => panic!("expected an error, got {:?}", config),
+                Err(err) => err.to_string(),
+            }
+        }
+
+        // Writes a file into a directory of its own for the test
+        fn write(test: &str, name: &str, contents: &str) -> String {
+            let directory = std::env::temp_dir().join(format!("cluster_reader_config_{}_{}", std::process::id(), test));
+            fs::create_dir_all(&directory).unwrap();
+
+            let location = directory.join(name);
+            fs::write(&location, contents).unwrap();
+
+            location.to_string_lossy().to_string()
+        }
+
+        #[test]
+        fn flags_override_the_environment_which_overrides_the_file() {
+            let config_file = write("layers", "config.toml", &format!(r#"
+                fixtures = "{}"
+                port = 9000
+                workers = 3
+                cache_ttl = 20
+                cors_origins = ["https://a.example"]
+                watch = false
+            "#, FIXTURES));
+
+            let config = load(&["--config", &config_file, "--port", "9200"], &[
+                ("CLUSTER_READER_PORT", "9100"),
+                ("CLUSTER_READER_WORKERS", "4"),
+                ("CLUSTER_READER_CORS_ORIGINS", "https://b.example,https://c.example"),
+                ("CLUSTER_READER_WATCH", "true"),
+            ]).unwrap();
+
+            assert_eq!(config.port, 9200);
+            assert_eq!(config.workers, 4);
+            assert_eq!(config.cache_ttl, Duration::from_secs(20));
+            assert_eq!(config.cors_origins, ["https://b.example", "https://c.example"]);
+            assert!(config.watch);
+            assert_eq!(config.json_limit, 4096);
+            assert_eq!(config.clusters[0].fixtures.as_deref(), Some(FIXTURES));
+        }
+
+        #[test]
+        fn defaults_apply_without_any_settings() {
+            let config = load(&["--fixtures", FIXTURES], &[]).unwrap();
+
+            assert_eq!(config.bind_address, "127.0.0.1");
+            assert_eq!(config.port, 8080);
+            assert_eq!(config.workers, 2);
+            assert_eq!(config.cors_origins, ["*"]);
+            assert_eq!(config.max_concurrency, 8);
+            assert_eq!(config.command_timeout, Duration::from_secs(30));
+            assert_eq!(config.cache_ttl, Duration::from_secs(10));
+            assert!(config.watch);
+            assert_eq!(config.resync_interval, Duration::from_secs(300));
+            assert_eq!(config.source, SourceKind::Api);
+            assert_eq!(config.default_cluster, "default");
+            assert_eq!(config.clusters.len(), 1);
+        }
+
+        #[test]
+        fn watch_is_a_plain_flag_that_also_takes_a_value() {
+            let watch = |args: &[&str], vars: &[(&str, &str)]| {
+                let args: Vec<&str> = ["--fixtures", FIXTURES].iter().chain(args).copied().collect();
+                load(&args, vars).unwrap().watch
+            };
+
+            assert!(watch(&["--watch"], &[("CLUSTER_READER_WATCH", "false")]));
+            assert!(!watch(&["--watch", "false"], &[]));
+            assert!(!watch(&["--watch=false"], &[]));
+            assert!(!watch(&[], &[("CLUSTER_READER_WATCH", "false")]));
+        }
+
+        #[test]
+        fn kubeconfig_defaults_to_kubectls_lookup() {
+            let kubeconfig = |vars: &[(&str, &str)]| load(&["--fixtures", FIXTURES], vars).unwrap().clusters[0].kubeconfig.clone();
+
+            assert_eq!(kubeconfig(&[("KUBECONFIG", "/etc/one.yaml:/etc/two.yaml"), ("HOME", "/home/reader")]), "/etc/one.yaml");
+            assert_eq!(kubeconfig(&[("HOME", "/home/reader")]), "/home/reader/.kube/config");
+            assert_eq!(kubeconfig(&[("CLUSTER_READER_KUBECONFIG", "/etc/reader.yaml"), ("KUBECONFIG", "/etc/one.yaml")]), "/etc/reader.yaml");
+        }
+
+        #[test]
+        fn clusters_fall_back_to_the_top_level_settings() {
+            let kubeconfig = write("clusters", "kubeconfig", "");
+            let config_file = write("clusters", "config.toml", &format!(r#"
+                kubeconfig = "{}"
+                default_cluster = "prod"
+
+                [[clusters]]
+                name = "dev"
+                fixtures = "{}"
+
+                [[clusters]]
+                name = "prod"
+                context = "prod-admin"
+            "#, kubeconfig, FIXTURES));
+
+            let config = load(&["--config", &config_file], &[]).unwrap();
+
+            assert_eq!(config.default_cluster, "prod");
+            assert_eq!(config.clusters[0].fixtures.as_deref(), Some(FIXTURES));
+            assert_eq!(config.clusters[1].fixtures, None);
+            assert_eq!(config.clusters[1].kubeconfig, kubeconfig);
+            assert_eq!(config.clusters[1].context.as_deref(), Some("prod-admin"));
+        }
+
+        #[test]
+        fn rejects_invalid_settings() {
+            let kubeconfig = write("invalid", "kubeconfig", "");
+
+            let cases: &[(&[&str], &str)] = &[
+                (&["--workers", "0"], "workers must be at least 1"),
+                (&["--max-concurrency", "0"], "max_concurrency must be at least 1"),
+                (&["--command-timeout", "0"], "command_timeout must be at least 1 second"),
+                (&["--resync-interval", "0"], "resync_interval must be at least 1 second"),
+                (&["--json-limit", "0"], "json_limit must be at least 1 byte"),
+                (&["--bind-address", "not an address"], "bind_address not an address:8080 is not a usable address"),
+                (&["--cors-origin", "https://ok.example,ftp://files.example"], "cors origin ftp://files.example must be"),
+                (&["--default-cluster", "prod"], "default_cluster prod is not one of the configured clusters"),
+                (&["--port", "http"], "invalid value 'http' for '--port <PORT>'"),
+                (&["--source", "ssh"], "invalid value 'ssh' for '--source <SOURCE>'"),
+                (&["--bogus"], "unexpected argument '--bogus'"),
+            ];
+
+            for (args, expected) in cases {
+                let args: Vec<&str> = ["--fixtures", FIXTURES].iter().chain(args.iter()).copied().collect();
+                let message = error(&args, &[]);
+                assert!(message.contains(expected), "{:?}: {}", args, message);
+            }
+
+            let message = error(&["--fixtures", "/nonexistent/fixtures"], &[]);
+            assert!(message.contains("fixtures location /nonexistent/fixtures for cluster default is not a directory"), "{}", message);
+
+            let message = error(&["--kubeconfig", "/nonexistent/kubeconfig"], &[]);
+            assert!(message.contains("kubeconfig /nonexistent/kubeconfig for cluster default does not exist"), "{}", message);
+
+            let message = error(&["--kubeconfig", &kubeconfig, "--source", "kubectl", "--kubectl-path", "/nonexistent/kubectl"], &[]);
+            assert!(message.contains("kubectl_path /nonexistent/kubectl was not found"), "{}", message);
+
+            let message = error(&["--kubeconfig", &kubeconfig, "--source", "kubectl"], &[("PATH", "/nonexistent")]);
+            assert!(message.contains("kubectl_path kubectl was not found"), "{}", message);
+
+            let message = error(&["--fixtures", FIXTURES], &[("CLUSTER_READER_PORT", "eighty")]);
+            assert!(message.contains("invalid value 'eighty'"), "{}", message);
+        }
+
+        #[test]
+        fn rejects_invalid_config_files() {
+            let message = error(&["--config", "/nonexistent/config.toml"], &[]);
+            assert!(message.contains("Failed to read config file /nonexistent/config.toml"), "{}", message);
+
+            let config_file = write("invalid_files", "unknown.toml", "prot = 8080\n");
+            let message = error(&["--config", &config_file], &[]);
+            assert!(message.contains("Invalid config file") && message.contains("prot"), "{}", message);
+
+            let cases = [
+                (vec!["dev", "dev"], "cluster dev is configured more than once"),
+                (vec!["dev cluster"], "cluster name \"dev cluster\" may only contain"),
+            ];
+
+            for (index, (names, expected)) in cases.into_iter().enumerate() {
+                let contents: String = names
+                    .iter()
+                    .map(|name| format!("[[clusters]]\nname = \"{}\"\nfixtures = \"{}\"\n", name, FIXTURES))
+                    .collect();
+                let config_file = write("invalid_files", &format!("clusters-{}.toml", index), &contents);
+
+                let message = error(&["--config", &config_file], &[]);
+                assert!(message.contains(expected), "{}: {}", contents, message);
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+pub mod error {
+
+    use std::fmt;
+    use actix_web::http::StatusCode;
+    use actix_web::{HttpResponse, ResponseError};
+    use serde::Serialize;
+    use serde_json::Value;
+
+    use crate::cluster_source::cluster_source::CommandError;
+
+    // Every failure the reader reports. The variant decides the HTTP status and the "code"
+    // in the JSON body so callers can tell a down cluster from missing RBAC from a parser bug.
+    #[derive(Debug)]
+    pub enum ReaderError {
+        // The request itself is malformed, e.g. an unknown filter value
+        InvalidRequest { message: String },
+        KubeconfigInvalid { message: String },
+        ClusterUnreachable { message: String, details: Option<Value> },
+        Forbidden { message: String, details: Option<Value> },
+        NotFound { message: String, details: Option<Value> },
+        ParseFailure { message: String },
+        Timeout { message: String, details: Option<Value> },
+        // kubectl or the apiserver failed for a reason not covered above
+        UpstreamFailure { message: String, details: Option<Value> },
+        Internal { message: String },
+    }
+
+    #[derive(Serialize)]
+    struct ErrorBody<'a> {
+        code: &'a str,
+        message: &'a str,
+        details: Option<&'a Value>,
+    }
+
+    impl ReaderError {
+
+        pub fn invalid_request(message: impl Into<String>) -> ReaderError {
+            ReaderError::InvalidRequest { message: message.into() }
+        }
+
+        pub fn kubeconfig_invalid(message: impl Into<String>) -> ReaderError {
+            ReaderError::KubeconfigInvalid { message: message.into() }
+        }
+
+        pub fn not_found(message: impl Into<String>) -> ReaderError {
+            ReaderError::NotFound { message: message.into(), details: None }
+        }
+
+        pub fn parse_failure(message: impl Into<String>) -> ReaderError {
+            ReaderError::ParseFailure { message: message.into() }
+        }
+
+        pub fn internal(message: impl Into<String>) -> ReaderError {
+            ReaderError::Internal { message: message.into() }
+        }
+
+        pub fn code(&self) -> &'static str {
+            match self {
+                ReaderError::InvalidRequest { .. } => "invalid_request",
+                ReaderError::KubeconfigInvalid { .. } => "kubeconfig_invalid",
+                ReaderError::ClusterUnreachable { .. } => "cluster_unreachable",
+                ReaderError::Forbidden { .. } => "forbidden",
+                ReaderError::NotFound { .. } => "not_found",
+                ReaderError::ParseFailure { .. } => "parse_failure",
+                ReaderError::Timeout { .. } => "timeout",
+                ReaderError::UpstreamFailure { .. } => "upstream_failure",
+                ReaderError::Internal { .. } => "internal",
+            }
+        }
+
+        pub fn message(&self) -> &str {
+            match self {
+                ReaderError::InvalidRequest { message }
+                | ReaderError::KubeconfigInvalid { message }
+                | ReaderError::ClusterUnreachable { message, .. }
+                | ReaderError::Forbidden { message, .. }
+                | ReaderError::NotFound { message, .. }
+                | ReaderError::ParseFailure { message }
+                | ReaderError::Timeout { message, .. }
+                | ReaderError::UpstreamFailure { message, .. }
+                | ReaderError::Internal { message } => message,
+            }
+        }
+
+        pub fn details(&self) -> Option<&Value> {
+            match self {
+                ReaderError::ClusterUnreachable { details, .. }
+                | ReaderError::Forbidden { details, .. }
+                | ReaderError::NotFound { details, .. }
+                | ReaderError::Timeout { details, .. }
+                | ReaderError::UpstreamFailure { details, .. } => details.as_ref(),
+                ReaderError::InvalidRequest { .. }
+                | ReaderError::KubeconfigInvalid { .. }
+                | ReaderError::ParseFailure { .. }
+                | ReaderError::Internal { .. } => None,
+            }
+        }
+    }
+
+    impl fmt::Display for ReaderError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.message())
+        }
+    }
+
+    impl std::error::Error for ReaderError {}
+
+    impl ResponseError for ReaderError {
+
+        fn status_code(&self) -> StatusCode {
+            match self {
+                ReaderError::InvalidRequest { .. } => StatusCode::BAD_REQUEST,
+                ReaderError::ClusterUnreachable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+                ReaderError::Forbidden { .. } => StatusCode::FORBIDDEN,
+                ReaderError::NotFound { .. } => StatusCode::NOT_FOUND,
+                ReaderError::Timeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+                ReaderError::UpstreamFailure { .. } => StatusCode::BAD_GATEWAY,
+                ReaderError::KubeconfigInvalid { .. }
+                | ReaderError::ParseFailure { .. }
+                | ReaderError::Internal { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        }
+
+        fn error_response(&self) -> HttpResponse {
+            HttpResponse::build(self.status_code()).json(ErrorBody {
+                code: self.code(),
+                message: self.message(),
+                details: self.details(),
+            })
+        }
+    }
+
+    impl From<std::io::Error> for ReaderError {
+        fn from(err: std::io::Error) -> ReaderError {
+            ReaderError::internal(err.to_string())
+        }
+    }
+
+    // kubectl only tells us what went wrong through stderr, so the common messages are
+    // matched to sort failures into the same variants the apiserver client uses
+    impl From<CommandError> for ReaderError {
+        fn from(err: CommandError) -> ReaderError {
+
+            let message = err.to_string();
+            let details = serde_json::to_value(&err).ok();
+
+            let stderr = match &err {
+                CommandError::Timeout { .. } => return ReaderError::Timeout { message, details },
+                CommandError::Failed { stderr, .. } => stderr.to_lowercase(),
+            };
+
+            if stderr.contains("forbidden") || stderr.contains("unauthorized") || stderr.contains("must be logged in") {
+                ReaderError::Forbidden { message, details }
+            } else if stderr.contains("notfound") || stderr.contains("not found") {
+                ReaderError::NotFound { message, details }
+            } else if stderr.contains("unable to connect") || stderr.contains("connection refused") || stderr.contains("no such host") || stderr.contains("i/o timeout") {
+                ReaderError::ClusterUnreachable { message, details }
+            } else {
+                ReaderError::UpstreamFailure { message, details }
+            }
+        }
+    }
+}
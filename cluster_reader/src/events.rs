@@ -0,0 +1,219 @@
+pub mod events {
+
+    use std::time::Duration;
+    use actix_web::web::Bytes;
+    use futures::stream::{self, Stream};
+    use serde::Serialize;
+    use tokio::sync::broadcast;
+    use tokio::sync::broadcast::error::RecvError;
+
+    use crate::cluster_source::cluster_source::ResourceKind;
+    use crate::error::error::ReaderError;
+    use crate::informer::informer::{ChangeType, ChangedObject, ClusterChange};
+    use crate::use_case::use_case::{process_deployment, process_node, DeploymentDetailGroups, FinalNodeValues};
+
+    // Proxies tend to drop connections that stay silent for a minute
+    const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+    // Which changes a subscriber wants. The namespace filter applies to namespaced kinds and to the
+    // namespace itself, nodes are never filtered by namespace.
+    pub struct EventFilter {
+        kinds: Vec<ResourceKind>,
+        namespace: Option<String>,
+    }
+
+    impl EventFilter {
+
+        // `resources` is a comma separated list of kinds, all kinds when absent
+        pub fn new(resources: Option<&str>, namespace: Option<String>) -> Result<EventFilter, ReaderError> {
+
+            let kinds = match resources {
+                Some(resources) => resources
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(|name| ResourceKind::from_name(name)
+                        .ok_or_else(|| ReaderError::invalid_request(format!("Unknown resource {}, expected nodes, namespaces or deployments", name))))
+                    .collect::<Result<Vec<ResourceKind>, ReaderError>>()?,
+                None => Vec::new(),
+            };
+
+            Ok(EventFilter { kinds, namespace })
+        }
+
+        fn matches(&self, change: &ClusterChange) -> bool {
+
+            if !self.kinds.is_empty() && !self.kinds.contains(&change.kind) {
+                return false;
+            }
+
+            match (&self.namespace, change.kind) {
+                (None, _) | (Some(_), ResourceKind::Nodes) => true,
+                (Some(namespace), ResourceKind::Namespaces) => &change.name == namespace,
+                (Some(namespace), _) => &change.namespace == namespace,
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    struct ChangeEvent<'a> {
+        resource: &'a str,
+        #[serde(rename = "type")]
+        change: ChangeType,
+        #[serde(skip_serializing_if = "str::is_empty")]
+        namespace: &'a str,
+        name: &'a str,
+        object: EventObject<'a>,
+    }
+
+    // Same shape as the matching entry of /cluster-info or /namespaces/deployment-details
+    #[derive(Serialize)]
+    #[serde(untagged)]
+    enum EventObject<'a> {
+        Node(Box<FinalNodeValues>),
+        Namespace { namespace: &'a str },
+        Deployment(DeploymentDetailGroups),
+    }
+
+    // Turns the informer's changes into a text/event-stream body. A subscriber that falls too far
+    // behind gets a "resync" event telling it to fetch the full state again.
+    pub fn change_stream(receiver: broadcast::Receiver<ClusterChange>, filter: EventFilter) -> impl Stream<Item = Result<Bytes, ReaderError>> {
+
+        stream::unfold((receiver, filter), |(mut receiver, filter)| async move {
+            loop {
+                let frame = match tokio::time::timeout(KEEP_ALIVE, receiver.recv()).await {
+                    Err(_) => String::from(": keep-alive\n\n"),
+                    Ok(Ok(change)) if filter.matches(&change) => change_frame(&change),
+                    Ok(Ok(_)) => continue,
+                    Ok(Err(RecvError::Lagged(missed))) => format!("event: resync\ndata: {{\"missed\":{}}}\n\n", missed),
+                    Ok(Err(RecvError::Closed)) => return None,
+                };
+
+                return Some((Ok(Bytes::from(frame)), (receiver, filter)));
+            }
+        })
+    }
+
+    fn change_frame(change: &ClusterChange) -> String {
+
+        let object = match &change.object {
+            ChangedObject::Node(node) => EventObject::Node(Box::new(process_node(node, None))),
+            ChangedObject::Namespace => EventObject::Namespace { namespace: &change.name },
+            ChangedObject::Deployment(deployment) => EventObject::Deployment(process_deployment(&change.name, deployment, None)),
+        };
+
+        let event = ChangeEvent {
+            resource: change.kind.name(),
+            change: change.change,
+            namespace: &change.namespace,
+            name: &change.name,
+            object,
+        };
+
+        let data = serde_json::to_string(&event).unwrap_or_default();
+
+        format!("event: {}\ndata: {}\n\n", change.kind.name(), data)
+    }
+
+/**********************************************************************************************************/
+
+    #[cfg(test)]
+    mod tests {
+
+        use futures::StreamExt;
+        use serde_json::json;
+
+        use super::*;
+
+        fn change(kind: ResourceKind, namespace: &str, name: &str) -> ClusterChange {
+            let object = json!({ "metadata": { "namespace": namespace, "name": name } });
+
+            ClusterChange {
+                kind,
+                change: ChangeType::Modified,
+                namespace: String::from(namespace),
+                name: String::from(name),
+                object: match kind {
+                    ResourceKind::Nodes => ChangedObject::Node(serde_json::from_value(object).unwrap()),
+                    ResourceKind::Namespaces => ChangedObject::Namespace,
+                    ResourceKind::Deployments => ChangedObject::Deployment(serde_json::from_value(object).unwrap()),
+                },
+            }
+        }
+
+        // Whether a node, the apps namespace, a deployment in apps and one in kube-system get through
+        fn matches(filter: &EventFilter) -> [bool; 4] {
+            [
+                filter.matches(&change(ResourceKind::Nodes, "", "worker-1")),
+                filter.matches(&change(ResourceKind::Namespaces, "", "apps")),
+                filter.matches(&change(ResourceKind::Deployments, "apps", "web")),
+                filter.matches(&change(ResourceKind::Deployments, "kube-system", "coredns")),
+            ]
+        }
+
+        #[test]
+        fn filters_by_kind() {
+            assert_eq!(matches(&EventFilter::new(None, None).unwrap()), [true, true, true, true]);
+            assert_eq!(matches(&EventFilter::new(Some("nodes"), None).unwrap()), [true, false, false, false]);
+            assert_eq!(matches(&EventFilter::new(Some(" deployments, namespaces ,"), None).unwrap()), [false, true, true, true]);
+        }
+
+        #[test]
+        fn filters_namespaces_and_deployments_by_namespace_but_never_nodes() {
+            assert_eq!(matches(&EventFilter::new(None, Some(String::from("apps"))).unwrap()), [true, true, true, false]);
+            assert_eq!(matches(&EventFilter::new(None, Some(String::from("kube-system"))).unwrap()), [true, false, false, true]);
+            assert_eq!(matches(&EventFilter::new(Some("namespaces"), Some(String::from("apps"))).unwrap()), [false, true, false, false]);
+        }
+
+        #[test]
+        fn rejects_unknown_resources() {
+            let err = EventFilter::new(Some("nodes,widgets"), None).err().unwrap();
+
+            assert!(matches!(&err, ReaderError::InvalidRequest { message } if message.contains("widgets")), "{:?}", err);
+        }
+
+        #[tokio::test]
+        async fn streams_matching_changes() {
+            let (sender, receiver) = broadcast::channel(4);
+            let filter = EventFilter::new(Some("deployments"), None).unwrap();
+            let mut frames = Box::pin(change_stream(receiver, filter));
+
+            sender.send(change(ResourceKind::Nodes, "", "worker-1")).unwrap();
+            sender.send(change(ResourceKind::Deployments, "apps", "web")).unwrap();
+
+            let frame = frames.next().await.unwrap().unwrap();
+            let frame = std::str::from_utf8(&frame).unwrap();
+
+            assert!(frame.starts_with("event: deployments\ndata: {\"resource\":\"deployments\",\"type\":\"modified\",\"namespace\":\"apps\",\"name\":\"web\","), "{}", frame);
+            assert!(frame.ends_with("\n\n"), "{}", frame);
+
+            drop(sender);
+            assert!(frames.next().await.is_none());
+        }
+
+        #[tokio::test]
+        async fn subscribers_that_fall_behind_are_told_to_resync() {
+            let (sender, receiver) = broadcast::channel(2);
+            let mut frames = Box::pin(change_stream(receiver, EventFilter::new(None, None).unwrap()));
+
+            for namespace in ["apps", "batch", "default", "kube-system", "monitoring"] {
+                sender.send(change(ResourceKind::Namespaces, "", namespace)).unwrap();
+            }
+
+            let frame = frames.next().await.unwrap().unwrap();
+            assert_eq!(frame, "event: resync\ndata: {\"missed\":3}\n\n");
+
+            // The stream carries on with the changes still buffered
+            let frame = frames.next().await.unwrap().unwrap();
+            assert_eq!(frame, "event: namespaces\ndata: {\"resource\":\"namespaces\",\"type\":\"modified\",\"name\":\"kube-system\",\"object\":{\"namespace\":\"kube-system\"}}\n\n");
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn quiet_streams_send_keep_alives() {
+            let (_sender, receiver) = broadcast::channel::<ClusterChange>(2);
+            let mut frames = Box::pin(change_stream(receiver, EventFilter::new(None, None).unwrap()));
+
+            assert_eq!(frames.next().await.unwrap().unwrap(), ": keep-alive\n\n");
+        }
+    }
+}
@@ -0,0 +1,704 @@
+pub mod informer {
+
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, RwLock};
+    use std::time::Duration;
+    use async_trait::async_trait;
+    use futures::StreamExt;
+    use serde::de::{DeserializeOwned, IgnoredAny};
+    use serde::Serialize;
+    use serde_json::Value;
+    use tokio::sync::broadcast;
+    use tokio::time::Instant;
+
+    use crate::cluster_source::cluster_source::{ClusterSource, ResourceKind, WatchEventType, WorkloadKind};
+    use crate::error::error::ReaderError;
+    use crate::kube_model::kube_model::{Deployment, EndpointSlice, Ingress, Node, Pod, Service, Workload};
+
+    const MIN_BACKOFF: Duration = Duration::from_secs(1);
+    const MAX_BACKOFF: Duration = Duration::from_secs(60);
+    // Changes a slow subscriber may fall behind by before it is told it missed some
+    const CHANGE_BUFFER: usize = 1024;
+
+    #[derive(Debug)]
+    #[derive(Clone, Copy, PartialEq)]
+    #[derive(Serialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum ChangeType {
+        Added,
+        Modified,
+        Deleted,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    pub enum ChangedObject {
+        Node(Node),
+        Namespace,
+        Deployment(Deployment),
+    }
+
+    // One object added to, changed in or removed from the model
+    #[derive(Debug)]
+    #[derive(Clone)]
+    pub struct ClusterChange {
+        pub kind: ResourceKind,
+        pub change: ChangeType,
+        // Empty for cluster scoped kinds
+        pub namespace: String,
+        pub name: String,
+        pub object: ChangedObject,
+    }
+
+    trait ModelObject: DeserializeOwned + Clone + Send + Sync + 'static {
+        fn changed(self) -> ChangedObject;
+    }
+
+    impl ModelObject for Node {
+        fn changed(self) -> ChangedObject {
+            ChangedObject::Node(self)
+        }
+    }
+
+    impl ModelObject for IgnoredAny {
+        fn changed(self) -> ChangedObject {
+            ChangedObject::Namespace
+        }
+    }
+
+    impl ModelObject for Deployment {
+        fn changed(self) -> ChangedObject {
+            ChangedObject::Deployment(self)
+        }
+    }
+
+    struct StoredObject<T> {
+        resource_version: String,
+        object: T,
+    }
+
+    // Objects of one kind keyed by (namespace, name), the namespace is empty for cluster scoped kinds
+    struct Store<T> {
+        objects: RwLock<BTreeMap<(String, String), StoredObject<T>>>,
+        // False until the first list completes and again after a failed watch, reads then go
+        // straight to the cluster so errors are reported instead of serving a stale model
+        synced: AtomicBool,
+    }
+
+    impl<T: Clone> Store<T> {
+
+        fn new() -> Store<T> {
+            Store {
+                objects: RwLock::new(BTreeMap::new()),
+                synced: AtomicBool::new(false),
+            }
+        }
+
+        fn synced(&self) -> bool {
+            self.synced.load(Ordering::Acquire)
+        }
+
+        fn set_synced(&self, synced: bool) {
+            self.synced.store(synced, Ordering::Release);
+        }
+
+        fn read(&self) -> std::sync::RwLockReadGuard<'_, BTreeMap<(String, String), StoredObject<T>>> {
+            self.objects.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+        }
+
+        fn write(&self) -> std::sync::RwLockWriteGuard<'_, BTreeMap<(String, String), StoredObject<T>>> {
+            self.objects.write().unwrap_or_else(|poisoned| poisoned.into_inner())
+        }
+
+        fn names(&self, namespace: &str) -> Vec<String> {
+            self.read()
+                .keys()
+                .filter(|(object_namespace, _)| object_namespace == namespace)
+                .map(|(_, name)| name.clone())
+                .collect()
+        }
+
+        fn get(&self, namespace: &str, name: &str) -> Option<T> {
+            self.read()
+                .get(&(String::from(namespace), String::from(name)))
+                .map(|stored| stored.object.clone())
+        }
+    }
+
+    // Keeps a live model of nodes, namespaces and deployments fed by list and watch, and answers
+    // from it once synced. Each kind is relisted every resync interval and after a watch fails.
+    pub struct InformerSource {
+        inner: Arc<dyn ClusterSource>,
+        nodes: Arc<Store<Node>>,
+        // Only the names of namespaces are needed
+        namespaces: Arc<Store<IgnoredAny>>,
+        deployments: Arc<Store<Deployment>>,
+        changes: broadcast::Sender<ClusterChange>,
+    }
+
+    impl InformerSource {
+
+        pub fn start(cluster_name: &str, inner: Box<dyn ClusterSource>, resync_interval: Duration) -> InformerSource {
+
+            let informer = InformerSource {
+                inner: Arc::from(inner),
+                nodes: Arc::new(Store::new()),
+                namespaces: Arc::new(Store::new()),
+                deployments: Arc::new(Store::new()),
+                changes: broadcast::channel(CHANGE_BUFFER).0,
+            };
+
+            let reflector = Reflector {
+                cluster_name: String::from(cluster_name),
+                source: informer.inner.clone(),
+                changes: informer.changes.clone(),
+                resync_interval,
+            };
+
+            tokio::spawn(reflector.clone().run(ResourceKind::Nodes, informer.nodes.clone()));
+            tokio::spawn(reflector.clone().run(ResourceKind::Namespaces, informer.namespaces.clone()));
+            tokio::spawn(reflector.run(ResourceKind::Deployments, informer.deployments.clone()));
+
+            informer
+        }
+    }
+
+    #[async_trait]
+    impl ClusterSource for InformerSource {
+
+        async fn list_nodes(&self) -> Result<Vec<String>, ReaderError> {
+            if !self.nodes.synced() {
+                return self.inner.list_nodes().await;
+            }
+
+            Ok(self.nodes.names(""))
+        }
+
+        async fn get_node(&self, node_name: &str) -> Result<Node, ReaderError> {
+            if !self.nodes.synced() {
+                return self.inner.get_node(node_name).await;
+            }
+
+            self.nodes
+                .get("", node_name)
+                .ok_or_else(|| ReaderError::not_found(format!("Node {} not found", node_name)))
+        }
+
+        async fn list_namespaces(&self) -> Result<Vec<String>, ReaderError> {
+            if !self.namespaces.synced() {
+                return self.inner.list_namespaces().await;
+            }
+
+            Ok(self.namespaces.names(""))
+        }
+
+        async fn list_deployments(&self, namespace: &str) -> Result<Vec<String>, ReaderError> {
+            if !self.deployments.synced() {
+                return self.inner.list_deployments(namespace).await;
+            }
+
+            Ok(self.deployments.names(namespace))
+        }
+
+        async fn get_deployment(&self, namespace: &str, deployment_name: &str) -> Result<Deployment, ReaderError> {
+            if !self.deployments.synced() {
+                return self.inner.get_deployment(namespace, deployment_name).await;
+            }
+
+            self.deployments
+                .get(namespace, deployment_name)
+                .ok_or_else(|| ReaderError::not_found(format!("Deployment {}/{} not found", namespace, deployment_name)))
+        }
+
+        // Pods change too often to be worth mirroring, they are always read from the cluster
+        async fn list_pods(&self, namespace: Option<&str>) -> Result<Vec<Pod>, ReaderError> {
+            self.inner.list_pods(namespace).await
+        }
+
+        async fn list_node_pods(&self, node_name: &str) -> Result<Vec<Pod>, ReaderError> {
+            self.inner.list_node_pods(node_name).await
+        }
+
+        // Networking objects aren't mirrored either
+        async fn list_services(&self, namespace: Option<&str>) -> Result<Vec<Service>, ReaderError> {
+            self.inner.list_services(namespace).await
+        }
+
+        async fn list_endpoint_slices(&self, namespace: Option<&str>) -> Result<Vec<EndpointSlice>, ReaderError> {
+            self.inner.list_endpoint_slices(namespace).await
+        }
+
+        async fn list_ingresses(&self, namespace: Option<&str>) -> Result<Vec<Ingress>, ReaderError> {
+            self.inner.list_ingresses(namespace).await
+        }
+
+        // Deployments are mirrored but the other kinds aren't, so all of them are listed the same way
+        async fn list_workloads(&self, kind: WorkloadKind, namespace: Option<&str>) -> Result<Vec<Workload>, ReaderError> {
+            self.inner.list_workloads(kind, namespace).await
+        }
+
+        fn subscribe(&self) -> Option<broadcast::Receiver<ClusterChange>> {
+            Some(self.changes.subscribe())
+        }
+    }
+
+/**********************************************************************************************************/
+
+    // Keeps one store in step with the cluster and reports what changed
+    #[derive(Clone)]
+    struct Reflector {
+        cluster_name: String,
+        source: Arc<dyn ClusterSource>,
+        changes: broadcast::Sender<ClusterChange>,
+        resync_interval: Duration,
+    }
+
+    impl Reflector {
+
+        async fn run<T: ModelObject>(self, kind: ResourceKind, store: Arc<Store<T>>) {
+
+            let mut backoff = MIN_BACKOFF;
+
+            loop {
+                match self.reflect(kind, &store).await {
+                    Ok(()) => backoff = MIN_BACKOFF,
+                    Err(err) => {
+                        store.set_synced(false);
+                        eprintln!("Watching {} on cluster {} failed, retrying in {}s: {}", kind.name(), self.cluster_name, backoff.as_secs(), err);
+
+                        tokio::time::sleep(backoff).await;
+                        backoff = (backoff * 2).min(MAX_BACKOFF);
+                    }
+                }
+            }
+        }
+
+        // Lists the kind into the store and then applies watch events until the resync interval is up,
+        // reconnecting from the last seen resource version whenever the apiserver closes the watch
+        async fn reflect<T: ModelObject>(&self, kind: ResourceKind, store: &Store<T>) -> Result<(), ReaderError> {
+
+            let list = self.source.list_objects(kind).await?;
+
+            // The apiserver leaves kind and apiVersion off list items
+            let item_kind = list.kind.strip_suffix("List").unwrap_or_default();
+
+            let mut objects = BTreeMap::new();
+            for mut item in list.items {
+                if let Some(fields) = item.as_object_mut() {
+                    if !item_kind.is_empty() {
+                        fields.entry("kind").or_insert_with(|| Value::from(item_kind));
+                        fields.entry("apiVersion").or_insert_with(|| Value::from(list.api_version.as_str()));
+                    }
+                }
+
+                let key = object_key(&item);
+                objects.insert(key, parse_object(kind, item)?);
+            }
+
+            self.replace(kind, store, objects);
+
+            let mut resource_version = list.metadata.resource_version.filter(|version| !version.is_empty());
+            let resync_at = Instant::now() + self.resync_interval;
+
+            loop {
+                let mut events = self.source.watch_objects(kind, resource_version.as_deref()).await?;
+
+                loop {
+                    let event = match tokio::time::timeout_at(resync_at, events.next()).await {
+                        Err(_) => return Ok(()),
+                        Ok(None) => break,
+                        Ok(Some(event)) => event?,
+                    };
+
+                    if let Some(version) = event.object.pointer("/metadata/resourceVersion").and_then(Value::as_str) {
+                        resource_version = Some(String::from(version));
+                    }
+
+                    match event.event_type {
+                        WatchEventType::Added | WatchEventType::Modified => {
+                            let key = object_key(&event.object);
+                            let stored: StoredObject<T> = parse_object(kind, event.object)?;
+                            let change = if store.read().contains_key(&key) { ChangeType::Modified } else { ChangeType::Added };
+
+                            self.notify(kind, change, &key, stored.object.clone());
+                            store.write().insert(key, stored);
+                        }
+                        WatchEventType::Deleted => {
+                            let key = object_key(&event.object);
+                            let stored: StoredObject<T> = parse_object(kind, event.object)?;
+
+                            store.write().remove(&key);
+                            self.notify(kind, ChangeType::Deleted, &key, stored.object);
+                        }
+                        WatchEventType::Bookmark => {}
+                        // 410 Gone means the resource version is too old to resume from, relist right away
+                        WatchEventType::Error if event.object.get("code").and_then(Value::as_u64) == Some(410) => return Ok(()),
+                        WatchEventType::Error => {
+                            let message = event.object.get("message").and_then(Value::as_str).unwrap_or("unknown error");
+                            return Err(ReaderError::UpstreamFailure {
+                                message: format!("Watch of {} failed: {}", kind.name(), message),
+                                details: Some(event.object),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        // Swaps in a fresh list, reporting whatever changed since the previous one. Nothing is reported
+        // for the first list since subscribers have had no model to compare against.
+        fn replace<T: ModelObject>(&self, kind: ResourceKind, store: &Store<T>, objects: BTreeMap<(String, String), StoredObject<T>>) {
+
+            let previous = std::mem::replace(&mut *store.write(), objects);
+            let was_synced = store.synced();
+            store.set_synced(true);
+
+            if !was_synced && previous.is_empty() {
+                return;
+            }
+
+            for (key, stored) in store.read().iter() {
+                match previous.get(key) {
+                    None => self.notify(kind, ChangeType::Added, key, stored.object.clone()),
+                    Some(old) if old.resource_version != stored.resource_version => self.notify(kind, ChangeType::Modified, key, stored.object.clone()),
+                    Some(_) => {}
+                }
+            }
+
+            for (key, old) in previous {
+                if !store.read().contains_key(&key) {
+                    self.notify(kind, ChangeType::Deleted, &key, old.object);
+                }
+            }
+        }
+
+        fn notify<T: ModelObject>(&self, kind: ResourceKind, change: ChangeType, key: &(String, String), object: T) {
+            // Sending only fails when nobody is subscribed
+            let _ = self.changes.send(ClusterChange {
+                kind,
+                change,
+                namespace: key.0.clone(),
+                name: key.1.clone(),
+                object: object.changed(),
+            });
+        }
+    }
+
+    fn object_key(object: &Value) -> (String, String) {
+        let field = |pointer: &str| object.pointer(pointer).and_then(Value::as_str).map(String::from).unwrap_or_default();
+
+        (field("/metadata/namespace"), field("/metadata/name"))
+    }
+
+    fn parse_object<T: DeserializeOwned>(kind: ResourceKind, object: Value) -> Result<StoredObject<T>, ReaderError> {
+
+        let resource_version = object
+            .pointer("/metadata/resourceVersion")
+            .and_then(Value::as_str)
+            .map(String::from)
+            .unwrap_or_default();
+
+        let object = serde_json::from_value(object)
+            .map_err(|err| ReaderError::parse_failure(format!("Failed to parse object from {} watch: {}", kind.name(), err)))?;
+
+        Ok(StoredObject { resource_version, object })
+    }
+
+/**********************************************************************************************************/
+
+    #[cfg(test)]
+    pub(crate) mod tests {
+
+        use std::collections::{HashMap, VecDeque};
+        use std::sync::Mutex;
+        use futures::channel::mpsc;
+        use futures::stream;
+        use serde_json::json;
+
+        use crate::cluster_source::cluster_source::{ResourceList, WatchEvent, WatchStream};
+        use super::*;
+
+        // A cluster that answers list and watch calls from a script and records every other call.
+        // A kind it has no more script for waits forever, like a quiet watch.
+        #[derive(Default)]
+        pub struct ScriptedSource {
+            lists: Mutex<HashMap<&'static str, VecDeque<ResourceList>>>,
+            watches: Mutex<HashMap<&'static str, VecDeque<WatchStream>>>,
+            // The resource version each watch was started from
+            watched_from: Arc<Mutex<Vec<Option<String>>>>,
+            calls: Arc<Mutex<Vec<String>>>,
+        }
+
+        impl ScriptedSource {
+
+            pub fn list(self, kind: ResourceKind, resource_version: &str, items: Vec<Value>) -> ScriptedSource {
+                let list = serde_json::from_value(json!({
+                    "kind": "List",
+                    "metadata": { "resourceVersion": resource_version },
+                    "items": items,
+                })).unwrap();
+
+                self.lists.lock().unwrap().entry(kind.name()).or_default().push_back(list);
+                self
+            }
+
+            pub fn watch(self, kind: ResourceKind, events: WatchStream) -> ScriptedSource {
+                self.watches.lock().unwrap().entry(kind.name()).or_default().push_back(events);
+                self
+            }
+
+            pub fn calls(&self) -> Arc<Mutex<Vec<String>>> {
+                self.calls.clone()
+            }
+
+            pub fn watched_from(&self) -> Arc<Mutex<Vec<Option<String>>>> {
+                self.watched_from.clone()
+            }
+
+            fn record(&self, call: String) {
+                self.calls.lock().unwrap().push(call);
+            }
+        }
+
+        pub fn event(event_type: &str, object: Value) -> Result<WatchEvent, ReaderError> {
+            Ok(serde_json::from_value(json!({ "type": event_type, "object": object })).unwrap())
+        }
+
+        // A watch the apiserver closes after these events
+        pub fn closing(events: Vec<Result<WatchEvent, ReaderError>>) -> WatchStream {
+            stream::iter(events).boxed()
+        }
+
+        // A watch that stays open after these events
+        pub fn open(events: Vec<Result<WatchEvent, ReaderError>>) -> WatchStream {
+            stream::iter(events).chain(stream::pending()).boxed()
+        }
+
+        pub fn object(namespace: &str, name: &str, resource_version: &str) -> Value {
+            json!({ "metadata": { "namespace": namespace, "name": name, "resourceVersion": resource_version } })
+        }
+
+        #[async_trait]
+        impl ClusterSource for ScriptedSource {
+
+            async fn list_nodes(&self) -> Result<Vec<String>, ReaderError> {
+                self.record(String::from("list_nodes"));
+                Ok(vec![String::from("from-cluster")])
+            }
+
+            async fn get_node(&self, node_name: &str) -> Result<Node, ReaderError> {
+                self.record(format!("get_node {}", node_name));
+                Ok(serde_json::from_value(object("", node_name, "1")).unwrap())
+            }
+
+            async fn list_namespaces(&self) -> Result<Vec<String>, ReaderError> {
+                self.record(String::from("list_namespaces"));
+                Ok(Vec::new())
+            }
+
+            async fn list_deployments(&self, namespace: &str) -> Result<Vec<String>, ReaderError> {
+                self.record(format!("list_deployments {}", namespace));
+                Ok(Vec::new())
+            }
+
+            async fn get_deployment(&self, namespace: &str, deployment_name: &str) -> Result<Deployment, ReaderError> {
+                self.record(format!("get_deployment {}/{}", namespace, deployment_name));
+                Err(ReaderError::not_found(format!("Deployment {}/{} not found", namespace, deployment_name)))
+            }
+
+            async fn list_pods(&self, namespace: Option<&str>) -> Result<Vec<Pod>, ReaderError> {
+                self.record(format!("list_pods {}", namespace.unwrap_or_default()));
+                Ok(Vec::new())
+            }
+
+            async fn list_node_pods(&self, node_name: &str) -> Result<Vec<Pod>, ReaderError> {
+                self.record(format!("list_node_pods {}", node_name));
+                Ok(Vec::new())
+            }
+
+            async fn list_services(&self, _namespace: Option<&str>) -> Result<Vec<Service>, ReaderError> {
+                Ok(Vec::new())
+            }
+
+            async fn list_endpoint_slices(&self, _namespace: Option<&str>) -> Result<Vec<EndpointSlice>, ReaderError> {
+                Ok(Vec::new())
+            }
+
+            async fn list_ingresses(&self, _namespace: Option<&str>) -> Result<Vec<Ingress>, ReaderError> {
+                Ok(Vec::new())
+            }
+
+            async fn list_workloads(&self, _kind: WorkloadKind, _namespace: Option<&str>) -> Result<Vec<Workload>, ReaderError> {
+                Ok(Vec::new())
+            }
+
+            async fn list_objects(&self, kind: ResourceKind) -> Result<ResourceList, ReaderError> {
+                let list = self.lists.lock().unwrap().get_mut(kind.name()).and_then(VecDeque::pop_front);
+
+                match list {
+                    Some(list) => Ok(list),
+                    None => futures::future::pending().await,
+                }
+            }
+
+            async fn watch_objects(&self, kind: ResourceKind, resource_version: Option<&str>) -> Result<WatchStream, ReaderError> {
+                self.watched_from.lock().unwrap().push(resource_version.map(String::from));
+                let events = self.watches.lock().unwrap().get_mut(kind.name()).and_then(VecDeque::pop_front);
+
+                match events {
+                    Some(events) => Ok(events),
+                    None => futures::future::pending().await,
+                }
+            }
+        }
+
+        fn reflector(source: ScriptedSource) -> (Reflector, broadcast::Receiver<ClusterChange>) {
+            let changes = broadcast::channel(16).0;
+            let receiver = changes.subscribe();
+
+            let reflector = Reflector {
+                cluster_name: String::from("test"),
+                source: Arc::new(source),
+                changes,
+                resync_interval: Duration::from_secs(300),
+            };
+
+            (reflector, receiver)
+        }
+
+        fn received(receiver: &mut broadcast::Receiver<ClusterChange>) -> Vec<(ChangeType, String, String)> {
+            std::iter::from_fn(|| receiver.try_recv().ok())
+                .map(|change| (change.change, change.namespace, change.name))
+                .collect()
+        }
+
+        fn change(change: ChangeType, namespace: &str, name: &str) -> (ChangeType, String, String) {
+            (change, String::from(namespace), String::from(name))
+        }
+
+        // What the apiserver sends when a watch asks for a resource version it no longer has
+        fn gone() -> Result<WatchEvent, ReaderError> {
+            event("ERROR", json!({ "kind": "Status", "code": 410, "reason": "Expired", "message": "too old resource version" }))
+        }
+
+        #[tokio::test]
+        async fn first_list_reports_nothing_and_a_relist_reports_the_difference() {
+            let source = ScriptedSource::default()
+                .list(ResourceKind::Nodes, "5", vec![object("", "worker-1", "5"), object("", "worker-2", "5")])
+                .watch(ResourceKind::Nodes, closing(vec![gone()]));
+            let (reflector, mut changes) = reflector(source);
+            let store = Store::<Node>::new();
+
+            reflector.reflect(ResourceKind::Nodes, &store).await.unwrap();
+
+            assert!(store.synced());
+            assert_eq!(store.names(""), ["worker-1", "worker-2"]);
+            assert_eq!(received(&mut changes), []);
+
+            // The 410 ended the watch without an error, so the next round relists right away
+            let source = ScriptedSource::default()
+                .list(ResourceKind::Nodes, "12", vec![object("", "worker-1", "7"), object("", "worker-3", "11")])
+                .watch(ResourceKind::Nodes, closing(vec![gone()]));
+            let reflector = Reflector { source: Arc::new(source), ..reflector };
+
+            reflector.reflect(ResourceKind::Nodes, &store).await.unwrap();
+
+            assert_eq!(store.names(""), ["worker-1", "worker-3"]);
+            assert_eq!(received(&mut changes), [
+                change(ChangeType::Modified, "", "worker-1"),
+                change(ChangeType::Added, "", "worker-3"),
+                change(ChangeType::Deleted, "", "worker-2"),
+            ]);
+        }
+
+        #[tokio::test]
+        async fn watch_events_update_the_model_and_resume_from_the_last_version() {
+            let source = ScriptedSource::default()
+                .list(ResourceKind::Deployments, "10", vec![object("apps", "web", "10")])
+                .watch(ResourceKind::Deployments, closing(vec![
+                    event("ADDED", object("apps", "api", "11")),
+                    event("MODIFIED", object("apps", "web", "12")),
+                    event("DELETED", object("apps", "api", "13")),
+                    event("BOOKMARK", json!({ "metadata": { "resourceVersion": "14" } })),
+                ]))
+                .watch(ResourceKind::Deployments, closing(vec![gone()]));
+            let watched_from = source.watched_from();
+            let (reflector, mut changes) = reflector(source);
+            let store = Store::<Deployment>::new();
+
+            reflector.reflect(ResourceKind::Deployments, &store).await.unwrap();
+
+            assert_eq!(*watched_from.lock().unwrap(), [Some(String::from("10")), Some(String::from("14"))]);
+            assert_eq!(store.names("apps"), ["web"]);
+            assert_eq!(store.read()[&(String::from("apps"), String::from("web"))].resource_version, "12");
+            assert_eq!(received(&mut changes), [
+                change(ChangeType::Added, "apps", "api"),
+                change(ChangeType::Modified, "apps", "web"),
+                change(ChangeType::Deleted, "apps", "api"),
+            ]);
+        }
+
+        #[tokio::test]
+        async fn other_watch_errors_fail_the_round() {
+            let source = ScriptedSource::default()
+                .list(ResourceKind::Namespaces, "3", vec![object("", "apps", "3")])
+                .watch(ResourceKind::Namespaces, closing(vec![
+                    event("ERROR", json!({ "kind": "Status", "code": 500, "message": "etcdserver: request timed out" })),
+                ]));
+            let (reflector, _changes) = reflector(source);
+            let store = Store::<IgnoredAny>::new();
+
+            let err = reflector.reflect(ResourceKind::Namespaces, &store).await.unwrap_err();
+
+            assert!(matches!(&err, ReaderError::UpstreamFailure { message, .. } if message.contains("etcdserver: request timed out")), "{:?}", err);
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn relists_after_the_resync_interval() {
+            let source = ScriptedSource::default()
+                .list(ResourceKind::Nodes, "5", vec![object("", "worker-1", "5")])
+                .watch(ResourceKind::Nodes, open(Vec::new()));
+            let (reflector, _changes) = reflector(source);
+            let store = Store::<Node>::new();
+            let started = Instant::now();
+
+            reflector.reflect(ResourceKind::Nodes, &store).await.unwrap();
+
+            assert_eq!(started.elapsed(), Duration::from_secs(300));
+        }
+
+        #[tokio::test]
+        async fn reads_go_to_the_cluster_until_synced_and_after_a_failed_watch() {
+            let (node_events, watch) = mpsc::unbounded();
+
+            let source = ScriptedSource::default()
+                .list(ResourceKind::Nodes, "5", vec![object("", "worker-1", "5")])
+                .watch(ResourceKind::Nodes, watch.boxed());
+            let calls = source.calls();
+            let informer = InformerSource::start("test", Box::new(source), Duration::from_secs(300));
+
+            // The reflectors haven't run yet
+            assert_eq!(informer.list_nodes().await.unwrap(), ["from-cluster"]);
+            assert_eq!(*calls.lock().unwrap(), ["list_nodes"]);
+
+            while !informer.nodes.synced() {
+                tokio::task::yield_now().await;
+            }
+
+            assert_eq!(informer.list_nodes().await.unwrap(), ["worker-1"]);
+            assert!(matches!(informer.get_node("worker-9").await, Err(ReaderError::NotFound { .. })));
+            assert_eq!(*calls.lock().unwrap(), ["list_nodes"]);
+
+            node_events.unbounded_send(event("ERROR", json!({ "code": 500, "message": "internal error" }))).unwrap();
+
+            while informer.nodes.synced() {
+                tokio::task::yield_now().await;
+            }
+
+            assert_eq!(informer.list_nodes().await.unwrap(), ["from-cluster"]);
+            assert_eq!(informer.get_node("worker-9").await.unwrap().metadata.name, "worker-9");
+            assert_eq!(*calls.lock().unwrap(), ["list_nodes", "list_nodes", "get_node worker-9"]);
+        }
+    }
+}
@@ -0,0 +1,773 @@
+pub mod kube_client {
+
+    use std::time::Duration;
+    use async_trait::async_trait;
+    use futures::stream::{self, StreamExt};
+    use reqwest::{Certificate, Client, Identity, StatusCode};
+    use serde::Deserialize;
+    use serde::de::DeserializeOwned;
+    use tokio::process::Command;
+    use tokio::sync::{RwLock, Semaphore};
+
+    use crate::cluster_source::cluster_source::{run_command, ClusterSource, ResourceKind, ResourceList, WatchEvent, WatchStream, WorkloadKind};
+    use crate::error::error::ReaderError;
+    use crate::kube_model::kube_model::{from_yaml, Deployment, EndpointSlice, Ingress, ItemList, Node, Pod, Service, Workload};
+    use crate::kubeconfig::kubeconfig::{ExecConfig, Kubeconfig};
+
+    // The apiserver ends each watch after this long and the informer starts a new one
+    const WATCH_TIMEOUT: Duration = Duration::from_secs(290);
+
+    enum Auth {
+        None,
+        Bearer(String),
+        Basic { username: String, password: Option<String> },
+        // Token from an exec plugin, re-run when the apiserver answers 401
+        Exec { config: ExecConfig, token: RwLock<String>, timeout: Duration },
+    }
+
+    pub struct KubeClient {
+        http: Client,
+        server: String,
+        auth: Auth,
+        // Caps how many requests are in flight to this apiserver at once
+        permits: Semaphore,
+    }
+
+    #[derive(Deserialize)]
+    struct ExecCredential {
+        status: Option<ExecCredentialStatus>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ExecCredentialStatus {
+        token: Option<String>,
+        client_certificate_data: Option<String>,
+        client_key_data: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct Status {
+        message: Option<String>,
+    }
+
+    impl KubeClient {
+
+        pub async fn from_kubeconfig(config_location: &str, context_name: Option<&str>, max_concurrency: usize, timeout: Duration) -> Result<KubeClient, ReaderError> {
+
+            let kubeconfig = Kubeconfig::read(config_location)?;
+            let context = kubeconfig.resolve_context(context_name)?;
+
+            let mut builder = Client::builder()
+                .use_rustls_tls()
+                .timeout(timeout);
+
+            if let Some(ca) = kubeconfig.load_bytes(&context.cluster.certificate_authority_data, &context.cluster.certificate_authority)? {
+                for certificate in Certificate::from_pem_bundle(&ca).map_err(http_error)? {
+                    builder = builder.add_root_certificate(certificate);
+                }
+            }
+
+            if context.cluster.insecure_skip_tls_verify {
+                builder = builder.danger_accept_invalid_certs(true);
+            }
+
+            let mut auth = Auth::None;
+
+            if let Some(user) = context.user {
+                let client_certificate = kubeconfig.load_bytes(&user.client_certificate_data, &user.client_certificate)?;
+                let client_key = kubeconfig.load_bytes(&user.client_key_data, &user.client_key)?;
+
+                if let (Some(certificate), Some(key)) = (client_certificate, client_key) {
+                    builder = builder.identity(client_identity(certificate, key)?);
+                }
+
+                if let Some(token) = &user.token {
+                    auth = Auth::Bearer(token.clone());
+                } else if let Some(token_file) = &user.token_file {
+                    let token = std::fs::read_to_string(kubeconfig.base_dir.join(token_file))
+                        .map_err(|err| ReaderError::kubeconfig_invalid(format!("Failed to read token file {}: {}", token_file, err)))?;
+                    auth = Auth::Bearer(String::from(token.trim()));
+                } else if let Some(exec) = &user.exec {
+                    let credential = run_exec_plugin(exec, timeout).await?;
+
+                    if let (Some(certificate), Some(key)) = (&credential.client_certificate_data, &credential.client_key_data) {
+                        builder = builder.identity(client_identity(certificate.clone().into_bytes(), key.clone().into_bytes())?);
+                    }
+
+                    if let Some(token) = credential.token {
+                        auth = Auth::Exec {
+                            config: exec.clone(),
+                            token: RwLock::new(token),
+                            timeout,
+                        };
+                    }
+                } else if let Some(username) = &user.username {
+                    auth = Auth::Basic {
+                        username: username.clone(),
+                        password: user.password.clone(),
+                    };
+                }
+            }
+
+            Ok(KubeClient {
+                http: builder.build().map_err(http_error)?,
+                server: String::from(context.cluster.server.trim_end_matches('/')),
+                auth,
+                permits: Semaphore::new(max_concurrency),
+            })
+        }
+
+        pub async fn get_text(&self, path: &str, accept: &str) -> Result<String, ReaderError> {
+
+            let _permit = self.permits
+                .acquire()
+                .await
+                .map_err(|_| ReaderError::internal("apiserver concurrency limit closed"))?;
+
+            let response = self.request(path, accept, None).await?;
+
+            response.text().await.map_err(http_error)
+        }
+
+        // Starts a watch and hands back the open response. The concurrency permit is only held until
+        // the apiserver answers since a watch stays open for minutes.
+        pub async fn watch(&self, path: &str) -> Result<reqwest::Response, ReaderError> {
+
+            let _permit = self.permits
+                .acquire()
+                .await
+                .map_err(|_| ReaderError::internal("apiserver concurrency limit closed"))?;
+
+            let separator = if path.contains('?') { '&' } else { '?' };
+            let path = format!("{}{}watch=1&allowWatchBookmarks=true&timeoutSeconds={}", path, separator, WATCH_TIMEOUT.as_secs());
+
+            self.request(&path, "application/json", Some(WATCH_TIMEOUT + Duration::from_secs(30))).await
+        }
+
+        // Sends the request, re-running the exec plugin once if the apiserver answers 401
+        async fn request(&self, path: &str, accept: &str, timeout: Option<Duration>) -> Result<reqwest::Response, ReaderError> {
+
+            let mut response = self.send(path, accept, timeout).await?;
+
+            if response.status() == StatusCode::UNAUTHORIZED {
+                if let Auth::Exec { config, token, timeout: exec_timeout } = &self.auth {
+                    let credential = run_exec_plugin(config, *exec_timeout).await?;
+                    if let Some(refreshed) = credential.token {
+                        *token.write().await = refreshed;
+                        response = self.send(path, accept, timeout).await?;
+                    }
+                }
+            }
+
+            let status = response.status();
+
+            if !status.is_success() {
+                let body = response.text().await.map_err(http_error)?;
+                return Err(status_error(status, path, &body));
+            }
+
+            Ok(response)
+        }
+
+        pub async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, ReaderError> {
+
+            let body = self.get_text(path, "application/json").await?;
+
+            serde_json::from_str(&body)
+                .map_err(|err| ReaderError::parse_failure(format!("Failed to parse response from {}: {}", path, err)))
+        }
+
+        async fn send(&self, path: &str, accept: &str, timeout: Option<Duration>) -> Result<reqwest::Response, ReaderError> {
+
+            let mut request = self.http
+                .get(format!("{}{}", self.server, path))
+                .header(reqwest::header::ACCEPT, accept);
+
+            if let Some(timeout) = timeout {
+                request = request.timeout(timeout);
+            }
+
+            let request = match &self.auth {
+                Auth::None => request,
+                Auth::Bearer(token) => request.bearer_auth(token),
+                Auth::Basic { username, password } => request.basic_auth(username, password.as_ref()),
+                Auth::Exec { token, .. } => request.bearer_auth(token.read().await.as_str()),
+            };
+
+            request.send().await.map_err(http_error)
+        }
+    }
+
+    fn client_identity(mut certificate: Vec<u8>, key: Vec<u8>) -> Result<Identity, ReaderError> {
+        certificate.push(b'\n');
+        certificate.extend(key);
+
+        Identity::from_pem(&certificate).map_err(http_error)
+    }
+
+    async fn run_exec_plugin(exec: &ExecConfig, timeout: Duration) -> Result<ExecCredentialStatus, ReaderError> {
+
+        let exec_info = serde_json::json!({
+            "apiVersion": exec.api_version.as_deref().unwrap_or("client.authentication.k8s.io/v1beta1"),
+            "kind": "ExecCredential",
+            "spec": { "interactive": false },
+        });
+
+        let mut command = Command::new(&exec.command);
+        command
+            .args(&exec.args)
+            .env("KUBERNETES_EXEC_INFO", exec_info.to_string());
+
+        for variable in &exec.env {
+            command.env(&variable.name, &variable.value);
+        }
+
+        let output = run_command(&mut command, &format!("exec plugin {}", exec.command), timeout).await?;
+
+        let credential: ExecCredential = serde_json::from_slice(&output.stdout)
+            .map_err(|err| ReaderError::kubeconfig_invalid(format!("Invalid ExecCredential from {}: {}", exec.command, err)))?;
+
+        credential
+            .status
+            .ok_or_else(|| ReaderError::kubeconfig_invalid(format!("ExecCredential from {} has no status", exec.command)))
+    }
+
+    fn http_error(err: reqwest::Error) -> ReaderError {
+
+        let message = err.to_string();
+
+        if err.is_connect() {
+            ReaderError::ClusterUnreachable { message, details: None }
+        } else if err.is_timeout() {
+            ReaderError::Timeout { message, details: None }
+        } else if err.is_builder() {
+            ReaderError::kubeconfig_invalid(message)
+        } else {
+            ReaderError::UpstreamFailure { message, details: None }
+        }
+    }
+
+    fn status_error(status: StatusCode, path: &str, body: &str) -> ReaderError {
+
+        let reason = serde_json::from_str::<Status>(body)
+            .ok()
+            .and_then(|status| status.message)
+            .unwrap_or_else(|| String::from(body.trim()));
+
+        let message = format!("GET {} returned {}: {}", path, status, reason);
+        let details = Some(serde_json::json!({
+            "path": path,
+            "status": status.as_u16(),
+            "reason": reason,
+        }));
+
+        match status {
+            StatusCode::NOT_FOUND => ReaderError::NotFound { message, details },
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ReaderError::Forbidden { message, details },
+            StatusCode::GATEWAY_TIMEOUT => ReaderError::Timeout { message, details },
+            StatusCode::SERVICE_UNAVAILABLE => ReaderError::ClusterUnreachable { message, details },
+            _ => ReaderError::UpstreamFailure { message, details },
+        }
+    }
+
+    // Escapes everything but unreserved characters so a name can't add segments or a query to the path,
+    // also used for query values
+    fn path_segment(segment: &str) -> String {
+        segment
+            .bytes()
+            .map(|byte| match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => char::from(byte).to_string(),
+                _ => format!("%{:02X}", byte),
+            })
+            .collect()
+    }
+
+/**********************************************************************************************************/
+
+    #[derive(Deserialize)]
+    struct ObjectList {
+        items: Vec<ObjectNameOnly>,
+    }
+
+    #[derive(Deserialize)]
+    struct ObjectNameOnly {
+        metadata: NameOnly,
+    }
+
+    #[derive(Deserialize)]
+    struct NameOnly {
+        name: String,
+    }
+
+    pub struct KubeApiSource {
+        client: KubeClient,
+    }
+
+    impl KubeApiSource {
+
+        pub fn new(client: KubeClient) -> KubeApiSource {
+            KubeApiSource { client }
+        }
+
+        async fn list_names(&self, path: &str) -> Result<Vec<String>, ReaderError> {
+
+            let list: ObjectList = self.client.get_json(path).await?;
+
+            let mut names: Vec<String> = list.items
+                .into_iter()
+                .map(|item| item.metadata.name)
+                .collect();
+
+            names.sort();
+
+            Ok(names)
+        }
+
+        // `group_version` is "api/v1" for the core group, "apis/<group>/<version>" for the others
+        async fn list_items<T: DeserializeOwned>(&self, group_version: &str, resource: &str, namespace: Option<&str>) -> Result<Vec<T>, ReaderError> {
+
+            let path = match namespace {
+                Some(namespace) => format!("/{}/namespaces/{}/{}", group_version, path_segment(namespace), resource),
+                None => format!("/{}/{}", group_version, resource),
+            };
+
+            let list: ItemList<T> = self.client.get_json(&path).await?;
+
+            Ok(list.items)
+        }
+    }
+
+    #[async_trait]
+    impl ClusterSource for KubeApiSource {
+
+        async fn list_nodes(&self) -> Result<Vec<String>, ReaderError> {
+            self.list_names("/api/v1/nodes").await
+        }
+
+        async fn get_node(&self, node_name: &str) -> Result<Node, ReaderError> {
+            self.client.get_json(&format!("/api/v1/nodes/{}", path_segment(node_name))).await
+        }
+
+        async fn list_namespaces(&self) -> Result<Vec<String>, ReaderError> {
+            self.list_names("/api/v1/namespaces").await
+        }
+
+        async fn list_deployments(&self, namespace: &str) -> Result<Vec<String>, ReaderError> {
+            self.list_names(&format!("/apis/apps/v1/namespaces/{}/deployments", path_segment(namespace))).await
+        }
+
+        async fn get_deployment(&self, namespace: &str, deployment_name: &str) -> Result<Deployment, ReaderError> {
+            let deployment = self.client
+                .get_text(&format!("/apis/apps/v1/namespaces/{}/deployments/{}", path_segment(namespace), path_segment(deployment_name)), "application/yaml")
+                .await?;
+
+            from_yaml(&format!("deployment {}/{}", namespace, deployment_name), &deployment)
+        }
+
+        async fn list_pods(&self, namespace: Option<&str>) -> Result<Vec<Pod>, ReaderError> {
+            self.list_items("api/v1", "pods", namespace).await
+        }
+
+        async fn list_node_pods(&self, node_name: &str) -> Result<Vec<Pod>, ReaderError> {
+            let field_selector = path_segment(&format!("spec.nodeName={}", node_name));
+            let list: ItemList<Pod> = self.client.get_json(&format!("/api/v1/pods?fieldSelector={}", field_selector)).await?;
+
+            Ok(list.items)
+        }
+
+        async fn list_services(&self, namespace: Option<&str>) -> Result<Vec<Service>, ReaderError> {
+            self.list_items("api/v1", "services", namespace).await
+        }
+
+        async fn list_endpoint_slices(&self, namespace: Option<&str>) -> Result<Vec<EndpointSlice>, ReaderError> {
+            self.list_items("apis/discovery.k8s.io/v1", "endpointslices", namespace).await
+        }
+
+        async fn list_ingresses(&self, namespace: Option<&str>) -> Result<Vec<Ingress>, ReaderError> {
+            self.list_items("apis/networking.k8s.io/v1", "ingresses", namespace).await
+        }
+
+        async fn list_workloads(&self, kind: WorkloadKind, namespace: Option<&str>) -> Result<Vec<Workload>, ReaderError> {
+            self.list_items(kind.group_version(), kind.name(), namespace)
+                .await?
+                .into_iter()
+                .map(|item| kind.parse(item))
+                .collect()
+        }
+
+        async fn list_objects(&self, kind: ResourceKind) -> Result<ResourceList, ReaderError> {
+            self.client.get_json(kind.api_path()).await
+        }
+
+        async fn watch_objects(&self, kind: ResourceKind, resource_version: Option<&str>) -> Result<WatchStream, ReaderError> {
+            let path = match resource_version {
+                Some(resource_version) => format!("{}?resourceVersion={}", kind.api_path(), path_segment(resource_version)),
+                None => String::from(kind.api_path()),
+            };
+
+            let response = self.client.watch(&path).await?;
+
+            Ok(watch_events(response, path))
+        }
+    }
+
+    // The apiserver writes one JSON event per line
+    fn watch_events(response: reqwest::Response, path: String) -> WatchStream {
+
+        stream::unfold(Some((response, Vec::new())), move |state| {
+            let path = path.clone();
+
+            async move {
+                let (mut response, mut buffer) = state?;
+
+                loop {
+                    if let Some(end) = buffer.iter().position(|byte| *byte == b'\n') {
+                        let line: Vec<u8> = buffer.drain(..=end).collect();
+                        if line.iter().all(u8::is_ascii_whitespace) {
+                            continue;
+                        }
+
+                        return match serde_json::from_slice::<WatchEvent>(&line) {
+                            Ok(event) => Some((Ok(event), Some((response, buffer)))),
+                            Err(err) => Some((Err(ReaderError::parse_failure(format!("Failed to parse event from {}: {}", path, err))), None)),
+                        };
+                    }
+
+                    match response.chunk().await {
+                        Ok(Some(chunk)) => buffer.extend_from_slice(&chunk),
+                        Ok(None) if buffer.iter().all(u8::is_ascii_whitespace) => return None,
+                        // The last event may come without a newline, it is parsed like any other line
+                        Ok(None) => buffer.push(b'\n'),
+                        Err(err) => return Some((Err(http_error(err)), None)),
+                    }
+                }
+            }
+        })
+        .boxed()
+    }
+
+/**********************************************************************************************************/
+
+    // The client against a stand-in apiserver on a local port serving responses recorded from a real one
+    #[cfg(test)]
+    mod tests {
+
+        use std::sync::{Arc, Mutex};
+        use futures::stream::StreamExt;
+        use serde_json::json;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpListener;
+
+        use super::*;
+        use crate::cluster_source::cluster_source::WatchEventType;
+
+        const RECORDED: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/apiserver");
+
+        fn recorded(name: &str) -> String {
+            std::fs::read_to_string(format!("{}/{}", RECORDED, name)).unwrap()
+        }
+
+        #[derive(Debug)]
+        #[derive(Clone)]
+        struct Request {
+            path: String,
+            authorization: Option<String>,
+            accept: Option<String>,
+        }
+
+        // A status and a body, written in pieces with a pause between them so the client reads
+        // them as separate chunks
+        type Response = (u16, Vec<String>);
+
+        // Answers every connection with `respond` and records what was asked. Returns the server URL.
+        async fn serve(respond: impl Fn(&Request) -> Response + Send + Sync + 'static) -> (String, Arc<Mutex<Vec<Request>>>) {
+
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let server = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let respond = Arc::new(respond);
+
+            let recorded_requests = requests.clone();
+            tokio::spawn(async move {
+                loop {
+                    let (mut connection, _) = listener.accept().await.unwrap();
+                    let requests = recorded_requests.clone();
+                    let respond = respond.clone();
+
+                    tokio::spawn(async move {
+                        let mut head = Vec::new();
+                        let mut byte = [0u8; 1];
+                        while !head.ends_with(b"\r\n\r\n") {
+                            if connection.read(&mut byte).await.unwrap() == 0 {
+                                return;
+                            }
+                            head.push(byte[0]);
+                        }
+
+                        let head = String::from_utf8(head).unwrap();
+                        let path = head.split(' ').nth(1).unwrap();
+                        let header = |name: &str| head
+                            .lines()
+                            .filter_map(|line| line.split_once(": "))
+                            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                            .map(|(_, value)| String::from(value));
+
+                        let request = Request {
+                            path: String::from(path),
+                            authorization: header("authorization"),
+                            accept: header("accept"),
+                        };
+                        requests.lock().unwrap().push(request.clone());
+
+                        let (status, pieces) = respond(&request);
+
+                        // No length, the body ends when the connection closes
+                        let status_line = format!("HTTP/1.1 {} Test\r\nContent-Type: application/json\r\nConnection: close\r\n\r\n", status);
+                        connection.write_all(status_line.as_bytes()).await.unwrap();
+
+                        for piece in pieces {
+                            connection.write_all(piece.as_bytes()).await.unwrap();
+                            connection.flush().await.unwrap();
+                            tokio::time::sleep(Duration::from_millis(10)).await;
+                        }
+                    });
+                }
+            });
+
+            (server, requests)
+        }
+
+        // An http:// kubeconfig for the stand-in, with `user` as its only user
+        fn kubeconfig(test: &str, server: &str, user: serde_json::Value) -> String {
+
+            let directory = std::env::temp_dir().join(format!("cluster_reader_kube_client_{}_{}", std::process::id(), test));
+            std::fs::create_dir_all(&directory).unwrap();
+
+            // Written as JSON, which is valid YAML
+            let config = json!({
+                "apiVersion": "v1",
+                "kind": "Config",
+                "current-context": "stand-in",
+                "clusters": [{ "name": "stand-in", "cluster": { "server": server } }],
+                "contexts": [{ "name": "stand-in", "context": { "cluster": "stand-in", "user": "reader" } }],
+                "users": [{ "name": "reader", "user": user }],
+            });
+
+            let location = directory.join("kubeconfig");
+            std::fs::write(&location, config.to_string()).unwrap();
+
+            location.to_string_lossy().to_string()
+        }
+
+        async fn api_source(test: &str, respond: impl Fn(&Request) -> Response + Send + Sync + 'static) -> (KubeApiSource, Arc<Mutex<Vec<Request>>>) {
+
+            let (server, requests) = serve(respond).await;
+            let location = kubeconfig(test, &server, json!({ "token": "recorded-token" }));
+
+            let client = KubeClient::from_kubeconfig(&location, None, 4, Duration::from_secs(5)).await.unwrap();
+
+            (KubeApiSource::new(client), requests)
+        }
+
+        fn paths(requests: &Mutex<Vec<Request>>) -> Vec<String> {
+            requests.lock().unwrap().iter().map(|request| request.path.clone()).collect()
+        }
+
+        #[tokio::test]
+        async fn lists_and_gets_objects() {
+
+            let (source, requests) = api_source("list_and_get", |request| match request.path.as_str() {
+                "/api/v1/nodes" => (200, vec![recorded("nodes.json")]),
+                "/api/v1/nodes/worker-1" => (200, vec![recorded("node-worker-1.json")]),
+                "/api/v1/pods?fieldSelector=spec.nodeName%3Dworker-1" => (200, vec![recorded("pods-worker-1.json")]),
+                "/apis/apps/v1/namespaces/default/deployments/web" => (200, vec![recorded("deployment-web.yaml")]),
+                _ => (404, vec![recorded("status-not-found.json")]),
+            }).await;
+
+            assert_eq!(source.list_nodes().await.unwrap(), ["control-plane-1", "worker-1"]);
+
+            let node = source.get_node("worker-1").await.unwrap();
+            assert_eq!(node.metadata.name, "worker-1");
+            assert_eq!(node.status.allocatable["memory"], "8129436Ki");
+            assert_eq!(node.status.node_info.kubelet_version, "v1.31.0");
+            assert!(node.is_ready());
+
+            let pods = source.list_node_pods("worker-1").await.unwrap();
+            assert_eq!(pods.len(), 1);
+            assert_eq!(pods[0].metadata.namespace.as_deref(), Some("default"));
+
+            let deployment = source.get_deployment("default", "web").await.unwrap();
+            assert_eq!(deployment.spec.replicas, Some(2));
+
+            let requests = requests.lock().unwrap();
+            assert!(requests.iter().all(|request| request.authorization.as_deref() == Some("Bearer recorded-token")));
+            assert_eq!(requests.last().unwrap().accept.as_deref(), Some("application/yaml"));
+        }
+
+        #[tokio::test]
+        async fn escapes_names_in_paths() {
+
+            let (source, requests) = api_source("escape", |_| (404, vec![recorded("status-not-found.json")])).await;
+
+            let _ = source.get_node("foo?labelSelector=x").await;
+            let _ = source.get_deployment("default", "../../nodes").await;
+
+            assert_eq!(paths(&requests), [
+                "/api/v1/nodes/foo%3FlabelSelector%3Dx",
+                "/apis/apps/v1/namespaces/default/deployments/..%2F..%2Fnodes",
+            ]);
+        }
+
+        #[tokio::test]
+        async fn maps_error_statuses() {
+
+            let (source, _) = api_source("errors", |request| match request.path.as_str() {
+                "/api/v1/nodes/unauthorized" => (401, vec![recorded("status-unauthorized.json")]),
+                "/api/v1/nodes/forbidden" => (403, vec![recorded("status-forbidden.json")]),
+                "/api/v1/nodes/unavailable" => (503, vec![String::from("etcdserver: leader changed")]),
+                "/api/v1/nodes/broken" => (500, vec![String::from("internal error")]),
+                _ => (404, vec![recorded("status-not-found.json")]),
+            }).await;
+
+            let error = |node_name: &'static str| {
+                let source = &source;
+                async move { source.get_node(node_name).await.unwrap_err() }
+            };
+
+            assert!(matches!(error("unauthorized").await, ReaderError::Forbidden { .. }));
+
+            let forbidden = error("forbidden").await;
+            assert!(matches!(forbidden, ReaderError::Forbidden { .. }));
+            assert!(forbidden.message().contains("cannot list resource"));
+            assert_eq!(forbidden.details().unwrap()["status"], 403);
+
+            let not_found = error("missing").await;
+            assert!(matches!(not_found, ReaderError::NotFound { .. }));
+            assert!(not_found.message().ends_with("nodes \"missing\" not found"));
+
+            assert!(matches!(error("unavailable").await, ReaderError::ClusterUnreachable { .. }));
+
+            let upstream = error("broken").await;
+            assert!(matches!(upstream, ReaderError::UpstreamFailure { .. }));
+            assert!(upstream.message().ends_with("internal error"));
+        }
+
+        #[tokio::test]
+        async fn refreshes_exec_token_on_401() {
+
+            // Only the second token the plugin hands out is accepted
+            let (server, requests) = serve(|request| match request.authorization.as_deref() {
+                Some("Bearer token-2") => (200, vec![recorded("nodes.json")]),
+                _ => (401, vec![recorded("status-unauthorized.json")]),
+            }).await;
+
+            let directory = std::env::temp_dir().join(format!("cluster_reader_kube_client_{}_exec", std::process::id()));
+            std::fs::create_dir_all(&directory).unwrap();
+            let count = directory.join("count");
+            let _ = std::fs::remove_file(&count);
+
+            let script = format!(
+                "n=$(( $(cat {count} 2>/dev/null || echo 0) + 1 )); echo $n > {count}; \
+                 printf '{{\"apiVersion\":\"client.authentication.k8s.io/v1beta1\",\"kind\":\"ExecCredential\",\"status\":{{\"token\":\"token-%s\"}}}}' $n",
+                count = count.display());
+
+            let location = kubeconfig("exec", &server, json!({
+                "exec": { "apiVersion": "client.authentication.k8s.io/v1beta1", "command": "sh", "args": ["-c", script] },
+            }));
+
+            let client = KubeClient::from_kubeconfig(&location, None, 4, Duration::from_secs(5)).await.unwrap();
+            let source = KubeApiSource::new(client);
+
+            assert_eq!(source.list_nodes().await.unwrap(), ["control-plane-1", "worker-1"]);
+            assert_eq!(std::fs::read_to_string(&count).unwrap().trim(), "2");
+
+            // The refreshed token is kept for later requests
+            source.list_nodes().await.unwrap();
+            assert_eq!(std::fs::read_to_string(&count).unwrap().trim(), "2");
+
+            let authorizations: Vec<Option<String>> = requests.lock().unwrap().iter().map(|request| request.authorization.clone()).collect();
+            assert_eq!(authorizations, [
+                Some(String::from("Bearer token-1")),
+                Some(String::from("Bearer token-2")),
+                Some(String::from("Bearer token-2")),
+            ]);
+        }
+
+        #[tokio::test]
+        async fn watch_events_are_split_at_line_boundaries() {
+
+            // Cut into pieces that end mid event and mid line
+            let events = recorded("watch-nodes.jsonl");
+            let pieces: Vec<String> = events
+                .as_bytes()
+                .chunks(37)
+                .map(|piece| String::from_utf8(piece.to_vec()).unwrap())
+                .collect();
+
+            let (source, requests) = api_source("watch", move |_| (200, pieces.clone())).await;
+
+            let events: Vec<WatchEvent> = source
+                .watch_objects(ResourceKind::Nodes, Some("48213"))
+                .await
+                .unwrap()
+                .map(Result::unwrap)
+                .collect()
+                .await;
+
+            let types: Vec<WatchEventType> = events.iter().map(|event| event.event_type).collect();
+            assert_eq!(types, [WatchEventType::Added, WatchEventType::Modified, WatchEventType::Bookmark, WatchEventType::Deleted]);
+            assert_eq!(events[1].object["spec"]["unschedulable"], true);
+
+            assert!(paths(&requests)[0].starts_with("/api/v1/nodes?resourceVersion=48213&watch=1&allowWatchBookmarks=true"));
+        }
+
+        #[tokio::test]
+        async fn watch_reports_a_broken_event() {
+
+            let (source, _) = api_source("broken_watch", |_| (200, vec![String::from("{\"type\":\"ADDED\",\"object\":{}}\nnot json\n")])).await;
+
+            let events: Vec<Result<WatchEvent, ReaderError>> = source
+                .watch_objects(ResourceKind::Nodes, None)
+                .await
+                .unwrap()
+                .collect()
+                .await;
+
+            assert_eq!(events.len(), 2);
+            assert!(events[0].is_ok());
+            assert!(matches!(events[1], Err(ReaderError::ParseFailure { .. })));
+        }
+
+        #[tokio::test]
+        async fn watch_keeps_a_last_event_without_a_newline() {
+
+            let body = "{\"type\":\"ADDED\",\"object\":{}}\n{\"type\":\"DELETED\",\"object\":{}}";
+            let (source, requests) = api_source("unterminated_watch", move |_| (200, vec![String::from(body)])).await;
+
+            let types: Vec<WatchEventType> = source
+                .watch_objects(ResourceKind::Nodes, Some("48213/a&b"))
+                .await
+                .unwrap()
+                .map(|event| event.unwrap().event_type)
+                .collect()
+                .await;
+
+            assert_eq!(types, [WatchEventType::Added, WatchEventType::Deleted]);
+            assert!(paths(&requests)[0].starts_with("/api/v1/nodes?resourceVersion=48213%2Fa%26b&watch=1"));
+
+            // A broken remainder is reported rather than dropped
+            let (source, _) = api_source("broken_remainder", |_| (200, vec![String::from("{\"type\":\"ADDED\",\"object\":{}}\n{\"type\":")])).await;
+
+            let events: Vec<Result<WatchEvent, ReaderError>> = source
+                .watch_objects(ResourceKind::Nodes, None)
+                .await
+                .unwrap()
+                .collect()
+                .await;
+
+            assert_eq!(events.len(), 2);
+            assert!(matches!(events[1], Err(ReaderError::ParseFailure { .. })));
+        }
+    }
+}
@@ -0,0 +1,990 @@
+pub mod kube_model {
+
+    use std::collections::BTreeMap;
+    use std::fmt;
+    use serde::{Deserialize, Deserializer};
+    use serde::de::DeserializeOwned;
+
+    use crate::error::error::ReaderError;
+
+    // Only the fields the reader uses are modeled, everything else in the object is ignored
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ObjectMeta {
+        #[serde(default)]
+        pub name: String,
+        #[serde(default)]
+        pub labels: BTreeMap<String, String>,
+        #[serde(default)]
+        pub annotations: BTreeMap<String, String>,
+        pub creation_timestamp: Option<String>,
+        pub namespace: Option<String>,
+        #[serde(default)]
+        pub owner_references: Vec<OwnerReference>,
+    }
+
+    impl ObjectMeta {
+
+        // The owner that manages the object, e.g. the replica set of a pod
+        pub fn controller(&self) -> Option<&OwnerReference> {
+            self.owner_references
+                .iter()
+                .find(|owner| owner.controller == Some(true))
+        }
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct OwnerReference {
+        pub kind: String,
+        pub name: String,
+        pub controller: Option<bool>,
+    }
+
+    // Lists from kubectl and the apiserver both carry their objects in "items"
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    pub struct ItemList<T> {
+        #[serde(default = "Vec::new")]
+        pub items: Vec<T>,
+    }
+
+    // Label selector as used by deployments and services
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct LabelSelector {
+        #[serde(default)]
+        pub match_labels: BTreeMap<String, String>,
+        #[serde(default)]
+        pub match_expressions: Vec<LabelSelectorRequirement>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct LabelSelectorRequirement {
+        pub key: String,
+        // In, NotIn, Exists or DoesNotExist
+        pub operator: String,
+        #[serde(default)]
+        pub values: Vec<String>,
+    }
+
+    impl LabelSelector {
+
+        // An empty selector matches everything, as it does in Kubernetes
+        pub fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+
+            let labels_match = self.match_labels
+                .iter()
+                .all(|(key, value)| labels.get(key) == Some(value));
+
+            let expressions_match = self.match_expressions
+                .iter()
+                .all(|requirement| {
+                    let value = labels.get(&requirement.key);
+                    match requirement.operator.as_str() {
+                        "In" => value.is_some_and(|value| requirement.values.contains(value)),
+                        "NotIn" => value.is_none_or(|value| !requirement.values.contains(value)),
+                        "Exists" => value.is_some(),
+                        "DoesNotExist" => value.is_none(),
+                        _ => false,
+                    }
+                });
+
+            labels_match && expressions_match
+        }
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct Node {
+        #[serde(default)]
+        pub metadata: ObjectMeta,
+        #[serde(default)]
+        pub spec: NodeSpec,
+        #[serde(default)]
+        pub status: NodeStatus,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    pub struct NodeSpec {
+        #[serde(default)]
+        pub taints: Vec<Taint>,
+        // Set by kubectl cordon
+        #[serde(default)]
+        pub unschedulable: bool,
+        // podCIDRs holds both families on dual-stack nodes, podCIDR only the first
+        #[serde(rename = "podCIDR")]
+        pub pod_cidr: Option<String>,
+        #[serde(default, rename = "podCIDRs")]
+        pub pod_cidrs: Vec<String>,
+        // The cloud provider's ID for the machine, e.g. aws:///us-east-1a/i-0123
+        #[serde(rename = "providerID")]
+        pub provider_id: Option<String>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct Taint {
+        pub key: String,
+        pub value: Option<String>,
+        // NoSchedule, PreferNoSchedule or NoExecute
+        pub effect: String,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    pub struct NodeStatus {
+        #[serde(default)]
+        pub capacity: BTreeMap<String, String>,
+        #[serde(default)]
+        pub allocatable: BTreeMap<String, String>,
+        #[serde(default)]
+        pub conditions: Vec<NodeCondition>,
+        #[serde(default, rename = "nodeInfo")]
+        pub node_info: NodeSystemInfo,
+        #[serde(default)]
+        pub addresses: Vec<NodeAddress>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct NodeAddress {
+        // InternalIP, ExternalIP, Hostname, InternalDNS or ExternalDNS
+        #[serde(rename = "type")]
+        pub address_type: String,
+        pub address: String,
+    }
+
+    // Empty where the kubelet doesn't report a value, kubeProxyVersion is no longer set by newer ones
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(default, rename_all = "camelCase")]
+    pub struct NodeSystemInfo {
+        #[serde(rename = "machineID")]
+        pub machine_id: String,
+        pub os_image: String,
+        pub kernel_version: String,
+        pub operating_system: String,
+        pub architecture: String,
+        pub container_runtime_version: String,
+        pub kubelet_version: String,
+        pub kube_proxy_version: String,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct NodeCondition {
+        #[serde(rename = "type")]
+        pub condition_type: String,
+        // "True", "False" or "Unknown"
+        pub status: String,
+        pub reason: Option<String>,
+        pub message: Option<String>,
+        pub last_transition_time: Option<String>,
+    }
+
+    impl Node {
+
+        // Same rules kubectl uses for the ROLES column
+        pub fn roles(&self) -> String {
+
+            let mut roles: Vec<&str> = self.metadata.labels
+                .keys()
+                .filter_map(|key| key.strip_prefix("node-role.kubernetes.io/"))
+                .filter(|role| !role.is_empty())
+                .collect();
+
+            if let Some(role) = self.metadata.labels.get("kubernetes.io/role") {
+                roles.push(role);
+            }
+
+            if roles.is_empty() {
+                String::from("<none>")
+            } else {
+                roles.join(",")
+            }
+        }
+
+        pub fn condition(&self, condition_type: &str) -> Option<&NodeCondition> {
+            self.status.conditions
+                .iter()
+                .find(|condition| condition.condition_type == condition_type)
+        }
+
+        pub fn pod_cidrs(&self) -> Vec<String> {
+            if self.spec.pod_cidrs.is_empty() {
+                self.spec.pod_cidr.iter().cloned().collect()
+            } else {
+                self.spec.pod_cidrs.clone()
+            }
+        }
+
+        pub fn is_ready(&self) -> bool {
+            self.condition("Ready").is_some_and(|condition| condition.status == "True")
+        }
+
+        // Whether new pods without tolerations can land here. PreferNoSchedule taints only steer the
+        // scheduler away, and not ready nodes are covered by the taints the node controller adds.
+        pub fn is_schedulable(&self) -> bool {
+            !self.spec.unschedulable && !self.spec.taints
+                .iter()
+                .any(|taint| taint.effect == "NoSchedule" || taint.effect == "NoExecute")
+        }
+    }
+
+/**********************************************************************************************************/
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Deployment {
+        #[serde(default)]
+        pub api_version: String,
+        #[serde(default)]
+        pub kind: String,
+        #[serde(default)]
+        pub metadata: ObjectMeta,
+        #[serde(default)]
+        pub spec: DeploymentSpec,
+        #[serde(default)]
+        pub status: DeploymentStatus,
+    }
+
+    // Replica sets have the same spec and status, less updatedReplicas
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    pub struct DeploymentSpec {
+        pub replicas: Option<i32>,
+        pub selector: Option<LabelSelector>,
+        #[serde(default)]
+        pub template: PodTemplateSpec,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DeploymentStatus {
+        pub ready_replicas: Option<i32>,
+        pub available_replicas: Option<i32>,
+        pub updated_replicas: Option<i32>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    pub struct PodTemplateSpec {
+        #[serde(default)]
+        pub spec: PodSpec,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PodSpec {
+        #[serde(default)]
+        pub init_containers: Vec<Container>,
+        #[serde(default)]
+        pub containers: Vec<Container>,
+        #[serde(default)]
+        pub ephemeral_containers: Vec<Container>,
+        // Only set on pods, once scheduled
+        pub node_name: Option<String>,
+        #[serde(default)]
+        pub node_selector: BTreeMap<String, String>,
+        // Resources the runtime itself uses per pod, on top of the containers
+        #[serde(default)]
+        pub overhead: BTreeMap<String, String>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Container {
+        #[serde(default)]
+        pub name: String,
+        pub image: Option<String>,
+        #[serde(default)]
+        pub ports: Vec<ContainerPort>,
+        #[serde(default)]
+        pub resources: ResourceRequirements,
+        // "Always" on an init container makes it a native sidecar
+        pub restart_policy: Option<String>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ContainerPort {
+        pub name: Option<String>,
+        pub container_port: i32,
+        pub protocol: Option<String>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    pub struct ResourceRequirements {
+        #[serde(default)]
+        pub limits: BTreeMap<String, String>,
+        #[serde(default)]
+        pub requests: BTreeMap<String, String>,
+    }
+
+/**********************************************************************************************************/
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct ReplicaSet {
+        #[serde(default)]
+        pub metadata: ObjectMeta,
+        #[serde(default)]
+        pub spec: DeploymentSpec,
+        #[serde(default)]
+        pub status: DeploymentStatus,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct StatefulSet {
+        #[serde(default)]
+        pub metadata: ObjectMeta,
+        #[serde(default)]
+        pub spec: StatefulSetSpec,
+        #[serde(default)]
+        pub status: DeploymentStatus,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StatefulSetSpec {
+        pub replicas: Option<i32>,
+        pub selector: Option<LabelSelector>,
+        #[serde(default)]
+        pub template: PodTemplateSpec,
+        pub service_name: Option<String>,
+        // OrderedReady or Parallel
+        pub pod_management_policy: Option<String>,
+        pub ordinals: Option<StatefulSetOrdinals>,
+        #[serde(default)]
+        pub volume_claim_templates: Vec<PersistentVolumeClaim>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct StatefulSetOrdinals {
+        #[serde(default)]
+        pub start: i32,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct PersistentVolumeClaim {
+        #[serde(default)]
+        pub metadata: ObjectMeta,
+        #[serde(default)]
+        pub spec: PersistentVolumeClaimSpec,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PersistentVolumeClaimSpec {
+        #[serde(default)]
+        pub access_modes: Vec<String>,
+        pub storage_class_name: Option<String>,
+        #[serde(default)]
+        pub resources: ResourceRequirements,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct DaemonSet {
+        #[serde(default)]
+        pub metadata: ObjectMeta,
+        #[serde(default)]
+        pub spec: DaemonSetSpec,
+        #[serde(default)]
+        pub status: DaemonSetStatus,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    pub struct DaemonSetSpec {
+        pub selector: Option<LabelSelector>,
+        #[serde(default)]
+        pub template: PodTemplateSpec,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct DaemonSetStatus {
+        pub desired_number_scheduled: Option<i32>,
+        pub current_number_scheduled: Option<i32>,
+        pub number_ready: Option<i32>,
+        pub number_available: Option<i32>,
+        // Running where the node selector no longer allows it
+        pub number_misscheduled: Option<i32>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct Job {
+        #[serde(default)]
+        pub metadata: ObjectMeta,
+        #[serde(default)]
+        pub spec: JobSpec,
+        #[serde(default)]
+        pub status: JobStatus,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct JobSpec {
+        pub completions: Option<i32>,
+        pub parallelism: Option<i32>,
+        pub backoff_limit: Option<i32>,
+        pub selector: Option<LabelSelector>,
+        #[serde(default)]
+        pub template: PodTemplateSpec,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct JobStatus {
+        pub active: Option<i32>,
+        pub succeeded: Option<i32>,
+        pub failed: Option<i32>,
+        pub start_time: Option<String>,
+        pub completion_time: Option<String>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct CronJob {
+        #[serde(default)]
+        pub metadata: ObjectMeta,
+        #[serde(default)]
+        pub spec: CronJobSpec,
+        #[serde(default)]
+        pub status: CronJobStatus,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CronJobSpec {
+        #[serde(default)]
+        pub schedule: String,
+        pub time_zone: Option<String>,
+        pub suspend: Option<bool>,
+        // Allow, Forbid or Replace
+        pub concurrency_policy: Option<String>,
+        #[serde(default)]
+        pub job_template: JobTemplateSpec,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    pub struct JobTemplateSpec {
+        #[serde(default)]
+        pub spec: JobSpec,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CronJobStatus {
+        // The jobs it is running right now
+        #[serde(default)]
+        pub active: Vec<ObjectReference>,
+        pub last_schedule_time: Option<String>,
+        pub last_successful_time: Option<String>,
+    }
+
+    // Any of the built-in kinds that run pods from a template
+    #[derive(Debug)]
+    #[derive(Clone)]
+    pub enum Workload {
+        Deployment(Deployment),
+        StatefulSet(StatefulSet),
+        DaemonSet(DaemonSet),
+        ReplicaSet(ReplicaSet),
+        Job(Job),
+        CronJob(CronJob),
+    }
+
+    impl Workload {
+
+        pub fn metadata(&self) -> &ObjectMeta {
+            match self {
+                Workload::Deployment(deployment) => &deployment.metadata,
+                Workload::StatefulSet(stateful_set) => &stateful_set.metadata,
+                Workload::DaemonSet(daemon_set) => &daemon_set.metadata,
+                Workload::ReplicaSet(replica_set) => &replica_set.metadata,
+                Workload::Job(job) => &job.metadata,
+                Workload::CronJob(cron_job) => &cron_job.metadata,
+            }
+        }
+
+        pub fn pod_spec(&self) -> &PodSpec {
+            match self {
+                Workload::Deployment(deployment) => &deployment.spec.template.spec,
+                Workload::StatefulSet(stateful_set) => &stateful_set.spec.template.spec,
+                Workload::DaemonSet(daemon_set) => &daemon_set.spec.template.spec,
+                Workload::ReplicaSet(replica_set) => &replica_set.spec.template.spec,
+                Workload::Job(job) => &job.spec.template.spec,
+                Workload::CronJob(cron_job) => &cron_job.spec.job_template.spec.template.spec,
+            }
+        }
+
+        // Cron jobs have none, their pods belong to the jobs they start
+        pub fn selector(&self) -> Option<&LabelSelector> {
+            match self {
+                Workload::Deployment(deployment) => deployment.spec.selector.as_ref(),
+                Workload::StatefulSet(stateful_set) => stateful_set.spec.selector.as_ref(),
+                Workload::DaemonSet(daemon_set) => daemon_set.spec.selector.as_ref(),
+                Workload::ReplicaSet(replica_set) => replica_set.spec.selector.as_ref(),
+                Workload::Job(job) => job.spec.selector.as_ref(),
+                Workload::CronJob(_) => None,
+            }
+        }
+    }
+
+/**********************************************************************************************************/
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct Pod {
+        #[serde(default)]
+        pub metadata: ObjectMeta,
+        #[serde(default)]
+        pub spec: PodSpec,
+        #[serde(default)]
+        pub status: PodStatus,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct PodStatus {
+        pub phase: Option<String>,
+        #[serde(rename = "hostIP")]
+        pub host_ip: Option<String>,
+        #[serde(default, rename = "podIPs")]
+        pub pod_ips: Vec<PodIp>,
+        pub start_time: Option<String>,
+        #[serde(default)]
+        pub init_container_statuses: Vec<ContainerStatus>,
+        #[serde(default)]
+        pub container_statuses: Vec<ContainerStatus>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct PodIp {
+        pub ip: String,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ContainerStatus {
+        pub name: String,
+        #[serde(default)]
+        pub ready: bool,
+        #[serde(default)]
+        pub restart_count: i32,
+        #[serde(default)]
+        pub state: ContainerState,
+    }
+
+    // At most one of these is set
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    pub struct ContainerState {
+        pub running: Option<ContainerStateDetail>,
+        pub waiting: Option<ContainerStateDetail>,
+        pub terminated: Option<ContainerStateDetail>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ContainerStateDetail {
+        pub reason: Option<String>,
+        pub exit_code: Option<i32>,
+    }
+
+    impl Pod {
+
+        // Succeeded and Failed pods no longer run anything
+        pub fn is_terminated(&self) -> bool {
+            matches!(self.status.phase.as_deref(), Some("Succeeded") | Some("Failed"))
+        }
+    }
+
+/**********************************************************************************************************/
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct Service {
+        #[serde(default)]
+        pub metadata: ObjectMeta,
+        #[serde(default)]
+        pub spec: ServiceSpec,
+        #[serde(default)]
+        pub status: ServiceStatus,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ServiceSpec {
+        // ClusterIP, NodePort, LoadBalancer or ExternalName
+        #[serde(rename = "type")]
+        pub service_type: Option<String>,
+        // "None" for headless services
+        #[serde(rename = "clusterIP")]
+        pub cluster_ip: Option<String>,
+        #[serde(default, rename = "externalIPs")]
+        pub external_ips: Vec<String>,
+        pub external_name: Option<String>,
+        #[serde(default)]
+        pub ports: Vec<ServicePort>,
+        // Services select pods by plain labels, without expressions
+        #[serde(default)]
+        pub selector: BTreeMap<String, String>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ServicePort {
+        pub name: Option<String>,
+        pub protocol: Option<String>,
+        pub port: i32,
+        pub target_port: Option<IntOrString>,
+        pub node_port: Option<i32>,
+    }
+
+    // Port fields that take either a number or the name of a container port
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    pub enum IntOrString {
+        Int(i64),
+        String(String),
+    }
+
+    impl fmt::Display for IntOrString {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                IntOrString::Int(value) => write!(f, "{}", value),
+                IntOrString::String(value) => write!(f, "{}", value),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ServiceStatus {
+        #[serde(default)]
+        pub load_balancer: LoadBalancerStatus,
+    }
+
+    // Services and ingresses report where their load balancer can be reached the same way
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    pub struct LoadBalancerStatus {
+        #[serde(default)]
+        pub ingress: Vec<LoadBalancerIngress>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct LoadBalancerIngress {
+        pub ip: Option<String>,
+        pub hostname: Option<String>,
+    }
+
+    impl LoadBalancerStatus {
+        pub fn addresses(&self) -> Vec<String> {
+            self.ingress
+                .iter()
+                .filter_map(|ingress| ingress.ip.clone().or_else(|| ingress.hostname.clone()))
+                .collect()
+        }
+    }
+
+    // discovery.k8s.io/v1, the service it belongs to is in the kubernetes.io/service-name label
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct EndpointSlice {
+        #[serde(default)]
+        pub metadata: ObjectMeta,
+        // Serialized as null rather than left out when the slice is empty
+        #[serde(default, deserialize_with = "null_as_default")]
+        pub endpoints: Vec<Endpoint>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Endpoint {
+        #[serde(default)]
+        pub addresses: Vec<String>,
+        #[serde(default)]
+        pub conditions: EndpointConditions,
+        pub node_name: Option<String>,
+        pub target_ref: Option<ObjectReference>,
+    }
+
+    // An unset condition counts as true
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    pub struct EndpointConditions {
+        pub ready: Option<bool>,
+        pub terminating: Option<bool>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct ObjectReference {
+        pub kind: Option<String>,
+        pub name: Option<String>,
+    }
+
+    impl ObjectReference {
+
+        // "Pod/web-abc", the way kubectl names objects
+        pub fn kind_and_name(&self) -> Option<String> {
+            match (&self.kind, &self.name) {
+                (Some(kind), Some(name)) => Some(format!("{}/{}", kind, name)),
+                _ => None,
+            }
+        }
+    }
+
+    // networking.k8s.io/v1
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct Ingress {
+        #[serde(default)]
+        pub metadata: ObjectMeta,
+        #[serde(default)]
+        pub spec: IngressSpec,
+        #[serde(default)]
+        pub status: IngressStatus,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct IngressStatus {
+        #[serde(default)]
+        pub load_balancer: LoadBalancerStatus,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct IngressSpec {
+        pub ingress_class_name: Option<String>,
+        pub default_backend: Option<IngressBackend>,
+        #[serde(default)]
+        pub rules: Vec<IngressRule>,
+        #[serde(default)]
+        pub tls: Vec<IngressTls>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct IngressRule {
+        pub host: Option<String>,
+        pub http: Option<HttpIngressRuleValue>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct HttpIngressRuleValue {
+        #[serde(default)]
+        pub paths: Vec<HttpIngressPath>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct HttpIngressPath {
+        pub path: Option<String>,
+        pub path_type: Option<String>,
+        pub backend: IngressBackend,
+    }
+
+    // Either a service or a resource such as a storage bucket
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct IngressBackend {
+        pub service: Option<IngressServiceBackend>,
+        pub resource: Option<ObjectReference>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct IngressServiceBackend {
+        pub name: String,
+        pub port: Option<ServiceBackendPort>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct ServiceBackendPort {
+        pub name: Option<String>,
+        pub number: Option<i32>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct IngressTls {
+        #[serde(default)]
+        pub hosts: Vec<String>,
+        pub secret_name: Option<String>,
+    }
+
+/**********************************************************************************************************/
+
+    fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Default + Deserialize<'de>,
+    {
+        Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+    }
+
+    pub fn from_json<T: DeserializeOwned>(what: &str, input: &[u8]) -> Result<T, ReaderError> {
+        serde_json::from_slice(input)
+            .map_err(|err| ReaderError::parse_failure(format!("Failed to parse {}: {}", what, err)))
+    }
+
+    pub fn from_yaml<T: DeserializeOwned>(what: &str, input: &str) -> Result<T, ReaderError> {
+        serde_yaml::from_str(input)
+            .map_err(|err| ReaderError::parse_failure(format!("Failed to parse {}: {}", what, err)))
+    }
+
+    // Names taken from the URL are checked before they reach the cluster. Nodes and most objects are
+    // named as DNS-1123 subdomains: dot separated labels of lowercase letters, digits and '-' that
+    // start and end with a letter or digit, at most 253 characters in all.
+    pub fn validate_name(what: &str, name: &str) -> Result<(), ReaderError> {
+
+        let valid_label = |label: &str| !label.is_empty()
+            && label.bytes().all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'-')
+            && !label.starts_with('-')
+            && !label.ends_with('-');
+
+        if name.len() <= 253 && name.split('.').all(valid_label) {
+            Ok(())
+        } else {
+            Err(ReaderError::invalid_request(format!("Invalid {} name {:?}, expected a DNS-1123 subdomain", what, name)))
+        }
+    }
+}
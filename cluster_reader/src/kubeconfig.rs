@@ -0,0 +1,507 @@
+pub mod kubeconfig {
+
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+    use serde::{Deserialize, Serialize};
+
+    use crate::error::error::ReaderError;
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct Kubeconfig {
+        #[serde(default)]
+        pub clusters: Vec<NamedCluster>,
+        #[serde(default)]
+        pub contexts: Vec<NamedContext>,
+        #[serde(default)]
+        pub users: Vec<NamedUser>,
+        pub current_context: Option<String>,
+
+        // Directory of the kubeconfig file, relative certificate paths are resolved against it
+        #[serde(skip)]
+        pub base_dir: PathBuf,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    pub struct NamedCluster {
+        pub name: String,
+        pub cluster: Cluster,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct Cluster {
+        pub server: String,
+        pub certificate_authority: Option<String>,
+        pub certificate_authority_data: Option<String>,
+        #[serde(default)]
+        pub insecure_skip_tls_verify: bool,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    pub struct NamedContext {
+        pub name: String,
+        pub context: Context,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    pub struct Context {
+        pub cluster: String,
+        pub user: Option<String>,
+        pub namespace: Option<String>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    pub struct NamedUser {
+        pub name: String,
+        #[serde(default)]
+        pub user: User,
+    }
+
+    #[derive(Debug)]
+    #[derive(Default)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub struct User {
+        pub token: Option<String>,
+        #[serde(rename = "tokenFile")]
+        pub token_file: Option<String>,
+        pub client_certificate: Option<String>,
+        pub client_certificate_data: Option<String>,
+        pub client_key: Option<String>,
+        pub client_key_data: Option<String>,
+        pub username: Option<String>,
+        pub password: Option<String>,
+        pub exec: Option<ExecConfig>,
+        // Legacy provider plugins (gcp, azure, oidc), only reported, never used to authenticate
+        pub auth_provider: Option<serde_yaml::Value>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct ExecConfig {
+        pub api_version: Option<String>,
+        pub command: String,
+        #[serde(default)]
+        pub args: Vec<String>,
+        #[serde(default)]
+        pub env: Vec<ExecEnvVar>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    pub struct ExecEnvVar {
+        pub name: String,
+        pub value: String,
+    }
+
+    // The cluster and user a context points at, resolved from their names
+    pub struct ResolvedContext<'a> {
+        pub cluster: &'a Cluster,
+        pub user: Option<&'a User>,
+    }
+
+    impl Kubeconfig {
+
+        pub fn read(config_location: &str) -> Result<Kubeconfig, ReaderError> {
+
+            let contents = fs::read_to_string(config_location)
+                .map_err(|err| ReaderError::kubeconfig_invalid(format!("Failed to read kubeconfig {}: {}", config_location, err)))?;
+
+            let mut kubeconfig: Kubeconfig = serde_yaml::from_str(&contents)
+                .map_err(|err| ReaderError::kubeconfig_invalid(format!("Failed to parse kubeconfig {}: {}", config_location, err)))?;
+
+            kubeconfig.base_dir = Path::new(config_location)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+
+            Ok(kubeconfig)
+        }
+
+        pub fn resolve_context(&self, context_name: Option<&str>) -> Result<ResolvedContext<'_>, ReaderError> {
+
+            let context_name = context_name
+                .or(self.current_context.as_deref())
+                .ok_or_else(|| ReaderError::kubeconfig_invalid("Kubeconfig has no current-context"))?;
+
+            let context = self.contexts
+                .iter()
+                .find(|context| context.name == context_name)
+                .ok_or_else(|| ReaderError::kubeconfig_invalid(format!("Context {} not found in kubeconfig", context_name)))?;
+
+            let cluster = self.clusters
+                .iter()
+                .find(|cluster| cluster.name == context.context.cluster)
+                .ok_or_else(|| ReaderError::kubeconfig_invalid(format!("Context {} references cluster {} which is not in the kubeconfig", context.name, context.context.cluster)))?;
+
+            let user = match &context.context.user {
+                Some(user_name) => Some(&self.users
+                    .iter()
+                    .find(|user| &user.name == user_name)
+                    .ok_or_else(|| ReaderError::kubeconfig_invalid(format!("Context {} references user {} which is not in the kubeconfig", context.name, user_name)))?
+                    .user),
+                None => None,
+            };
+
+            Ok(ResolvedContext {
+                cluster: &cluster.cluster,
+                user,
+            })
+        }
+
+        // Checks every reference in the file resolves so a broken kubeconfig fails at startup
+        // rather than on the first request
+        pub fn validate(&self, context_name: Option<&str>) -> Result<(), ReaderError> {
+
+            for cluster in &self.clusters {
+                if !cluster.cluster.server.starts_with("https://") && !cluster.cluster.server.starts_with("http://") {
+                    return Err(ReaderError::kubeconfig_invalid(format!("Cluster {} has server \"{}\", expected an http:// or https:// URL", cluster.name, cluster.cluster.server)));
+                }
+
+                self.load_bytes(&cluster.cluster.certificate_authority_data, &cluster.cluster.certificate_authority)
+                    .map_err(|err| ReaderError::kubeconfig_invalid(format!("Cluster {} certificate authority: {}", cluster.name, err)))?;
+            }
+
+            for context in &self.contexts {
+                self.resolve_context(Some(&context.name))?;
+            }
+
+            for user in &self.users {
+                let certificate = self.load_bytes(&user.user.client_certificate_data, &user.user.client_certificate);
+                let key = self.load_bytes(&user.user.client_key_data, &user.user.client_key);
+
+                match (certificate, key) {
+                    (Ok(Some(_)), Ok(None)) | (Ok(None), Ok(Some(_))) => {
+                        return Err(ReaderError::kubeconfig_invalid(format!("User {} needs both a client certificate and a client key", user.name)));
+                    },
+                    (Err(err), _) | (_, Err(err)) => {
+                        return Err(ReaderError::kubeconfig_invalid(format!("User {} client certificate: {}", user.name, err)));
+                    },
+                    _ => {}
+                }
+            }
+
+            self.resolve_context(context_name)?;
+
+            Ok(())
+        }
+
+        pub fn summary(&self) -> KubeconfigSummary {
+
+            let contexts = self.contexts
+                .iter()
+                .map(|context| ContextSummary {
+                    name: context.name.clone(),
+                    cluster: context.context.cluster.clone(),
+                    user: context.context.user.clone(),
+                    namespace: context.context.namespace.clone(),
+                    current: self.current_context.as_deref() == Some(context.name.as_str()),
+                })
+                .collect();
+
+            let clusters = self.clusters
+                .iter()
+                .map(|cluster| ClusterSummary {
+                    name: cluster.name.clone(),
+                    server: cluster.cluster.server.clone(),
+                    insecure_skip_tls_verify: cluster.cluster.insecure_skip_tls_verify,
+                })
+                .collect();
+
+            let users = self.users
+                .iter()
+                .map(|user| UserSummary {
+                    name: user.name.clone(),
+                    auth_methods: user.user.auth_methods(),
+                })
+                .collect();
+
+            KubeconfigSummary {
+                current_context: self.current_context.clone(),
+                contexts,
+                clusters,
+                users,
+            }
+        }
+
+        // Reads either the inline base64 data or the referenced file
+        pub fn load_bytes(&self, data: &Option<String>, file: &Option<String>) -> Result<Option<Vec<u8>>, ReaderError> {
+
+            if let Some(data) = data {
+                let decoded = STANDARD
+                    .decode(data.trim())
+                    .map_err(|err| ReaderError::kubeconfig_invalid(format!("Invalid base64 in kubeconfig: {}", err)))?;
+                return Ok(Some(decoded));
+            }
+
+            if let Some(file) = file {
+                let path = self.base_dir.join(file);
+                let contents = fs::read(&path)
+                    .map_err(|err| ReaderError::kubeconfig_invalid(format!("Failed to read {}: {}", path.display(), err)))?;
+                return Ok(Some(contents));
+            }
+
+            Ok(None)
+        }
+    }
+
+    impl User {
+
+        fn auth_methods(&self) -> Vec<AuthMethod> {
+
+            let mut methods = Vec::new();
+
+            if self.token.is_some() {
+                methods.push(AuthMethod::Token);
+            }
+            if self.token_file.is_some() {
+                methods.push(AuthMethod::TokenFile);
+            }
+            if self.client_certificate.is_some() || self.client_certificate_data.is_some() {
+                methods.push(AuthMethod::ClientCertificate);
+            }
+            if self.username.is_some() {
+                methods.push(AuthMethod::Basic);
+            }
+            if self.exec.is_some() {
+                methods.push(AuthMethod::Exec);
+            }
+            if self.auth_provider.is_some() {
+                methods.push(AuthMethod::AuthProvider);
+            }
+
+            methods
+        }
+    }
+
+/**********************************************************************************************************/
+
+    // What the API reports about a kubeconfig. Only names, server URLs and the kind of
+    // credentials are exposed, never the credentials themselves.
+
+    #[derive(Debug)]
+    #[derive(Serialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum AuthMethod {
+        Token,
+        TokenFile,
+        ClientCertificate,
+        Basic,
+        Exec,
+        AuthProvider,
+    }
+
+    #[derive(Debug)]
+    #[derive(Serialize)]
+    pub struct ContextSummary {
+        name: String,
+        cluster: String,
+        user: Option<String>,
+        namespace: Option<String>,
+        current: bool,
+    }
+
+    #[derive(Debug)]
+    #[derive(Serialize)]
+    pub struct ClusterSummary {
+        name: String,
+        server: String,
+        insecure_skip_tls_verify: bool,
+    }
+
+    #[derive(Debug)]
+    #[derive(Serialize)]
+    pub struct UserSummary {
+        name: String,
+        auth_methods: Vec<AuthMethod>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Serialize)]
+    pub struct KubeconfigSummary {
+        current_context: Option<String>,
+        contexts: Vec<ContextSummary>,
+        clusters: Vec<ClusterSummary>,
+        users: Vec<UserSummary>,
+    }
+
+    impl KubeconfigSummary {
+
+        pub fn contexts(self) -> Contexts {
+            Contexts {
+                current_context: self.current_context,
+                contexts: self.contexts,
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    #[derive(Serialize)]
+    pub struct Contexts {
+        current_context: Option<String>,
+        contexts: Vec<ContextSummary>,
+    }
+
+/**********************************************************************************************************/
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::*;
+
+        // "Y2VydA==" and "a2V5" are base64 for "cert" and "key"
+        const KUBECONFIG: &str = r#"
+apiVersion: v1
+kind: Config
+current-context: dev
+clusters:
+- name: dev
+  cluster:
+    server: https://10.0.0.1:6443
+    certificate-authority-data: Y2VydA==
+contexts:
+- name: dev
+  context:
+    cluster: dev
+    user: admin
+    namespace: apps
+users:
+- name: admin
+  user:
+    client-certificate-data: Y2VydA==
+    client-key-data: a2V5
+"#;
+
+        fn parse(kubeconfig: &str) -> Kubeconfig {
+            serde_yaml::from_str(kubeconfig).unwrap()
+        }
+
+        fn rejection(kubeconfig: &str, context_name: Option<&str>) -> String {
+            match parse(kubeconfig).validate(context_name) {
+                Err(ReaderError::KubeconfigInvalid { message }) => message,
+                other => panic!("expected kubeconfig_invalid, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn accepts_a_complete_kubeconfig() {
+            let kubeconfig = parse(KUBECONFIG);
+
+            kubeconfig.validate(None).unwrap();
+            kubeconfig.validate(Some("dev")).unwrap();
+        }
+
+        #[test]
+        fn rejects_dangling_references() {
+            let message = rejection(&KUBECONFIG.replace("    cluster: dev", "    cluster: staging"), None);
+            assert!(message.contains("references cluster staging"), "{}", message);
+
+            let message = rejection(&KUBECONFIG.replace("    user: admin", "    user: viewer"), None);
+            assert!(message.contains("references user viewer"), "{}", message);
+
+            let message = rejection(&KUBECONFIG.replace("current-context: dev", "current-context: prod"), None);
+            assert!(message.contains("Context prod not found"), "{}", message);
+
+            let message = rejection(KUBECONFIG, Some("prod"));
+            assert!(message.contains("Context prod not found"), "{}", message);
+        }
+
+        #[test]
+        fn rejects_servers_that_are_not_http_urls() {
+            for server in ["10.0.0.1:6443", "ftp://10.0.0.1", "unix:///var/run/k8s.sock"] {
+                let message = rejection(&KUBECONFIG.replace("https://10.0.0.1:6443", server), None);
+                assert!(message.contains("expected an http:// or https:// URL"), "{}", message);
+            }
+        }
+
+        #[test]
+        fn rejects_bad_base64() {
+            let message = rejection(&KUBECONFIG.replace("certificate-authority-data: Y2VydA==", "certificate-authority-data: not*base64"), None);
+            assert!(message.contains("Cluster dev certificate authority"), "{}", message);
+
+            let message = rejection(&KUBECONFIG.replace("client-key-data: a2V5", "client-key-data: a2V5!"), None);
+            assert!(message.contains("Invalid base64"), "{}", message);
+        }
+
+        #[test]
+        fn rejects_a_certificate_without_a_key() {
+            let message = rejection(&KUBECONFIG.replace("    client-key-data: a2V5\n", ""), None);
+            assert!(message.contains("needs both a client certificate and a client key"), "{}", message);
+
+            let message = rejection(&KUBECONFIG.replace("    client-certificate-data: Y2VydA==\n", ""), None);
+            assert!(message.contains("needs both a client certificate and a client key"), "{}", message);
+        }
+
+        #[test]
+        fn rejects_files_that_are_not_kubeconfigs() {
+            let config_location = std::env::temp_dir().join(format!("cluster_reader_kubeconfig_{}.yaml", std::process::id()));
+            fs::write(&config_location, "clusters: [").unwrap();
+
+            let result = Kubeconfig::read(config_location.to_str().unwrap());
+            fs::remove_file(&config_location).unwrap();
+
+            assert!(matches!(result, Err(ReaderError::KubeconfigInvalid { .. })), "{:?}", result);
+            assert!(matches!(Kubeconfig::read("/nonexistent/kubeconfig"), Err(ReaderError::KubeconfigInvalid { .. })));
+        }
+
+        #[test]
+        fn summaries_never_contain_credentials() {
+            let kubeconfig = parse(r#"
+current-context: dev
+clusters:
+- name: dev
+  cluster:
+    server: https://10.0.0.1:6443
+contexts:
+- name: dev
+  context:
+    cluster: dev
+    user: admin
+users:
+- name: admin
+  user:
+    token: secret-token-value
+    username: admin
+    password: secret-password-value
+    client-certificate-data: c2VjcmV0LWNlcnQtdmFsdWU=
+    client-key-data: c2VjcmV0LWtleS12YWx1ZQ==
+    exec:
+      apiVersion: client.authentication.k8s.io/v1
+      command: secret-exec-command
+      args: [--secret-exec-arg]
+      env:
+      - name: SECRET_ENV_NAME
+        value: secret-env-value
+"#);
+
+            let summary = serde_json::to_string(&kubeconfig.summary()).unwrap();
+
+            for secret in [
+                "secret-token-value",
+                "secret-password-value",
+                "c2VjcmV0LWNlcnQtdmFsdWU=",
+                "c2VjcmV0LWtleS12YWx1ZQ==",
+                "secret-exec-command",
+                "--secret-exec-arg",
+                "SECRET_ENV_NAME",
+                "secret-env-value",
+            ] {
+                assert!(!summary.contains(secret), "{} appears in {}", secret, summary);
+            }
+
+            assert!(summary.contains(r#""auth_methods":["token","client-certificate","basic","exec"]"#), "{}", summary);
+        }
+    }
+}
@@ -1,42 +1,78 @@
-use actix_web::{error, web, App, HttpResponse, HttpServer};
+#![allow(clippy::module_inception)]
+
+use actix_web::{error::InternalError, web, App, HttpResponse, HttpServer};
 use actix_cors::Cors;
 
+mod allocation;
 mod api;
 mod api_service;
+mod cluster_registry;
+mod cluster_source;
+mod config;
+mod error;
+mod events;
+mod informer;
+mod kube_client;
+mod kube_model;
+mod kubeconfig;
+mod network;
+mod pods;
+mod quantity;
+mod response_cache;
 mod use_case;
+mod workloads;
 use crate::api::api::scoped_config;
-use crate::api_service::api_service::check_config;
+use crate::cluster_registry::cluster_registry::ClusterRegistry;
+use crate::config::config::Config;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
 
-    const ENVIRONMENT_VARIABLE_KEY: &str = "KUBECONFIG";
-    const CONFIG_LOCATION: &str = "/home/joe/.kube/k3s.yaml";
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Invalid configuration: {}", err);
+            std::process::exit(2);
+        }
+    };
+
+    let registry = match ClusterRegistry::from_config(&config).await {
+        Ok(registry) => web::Data::new(registry),
+        Err(err) => {
+            eprintln!("Failed to set up clusters: {}", err);
+            std::process::exit(1);
+        }
+    };
 
-    let _ = check_config(&ENVIRONMENT_VARIABLE_KEY, &CONFIG_LOCATION)?;
+    let json_limit = config.json_limit;
+    let cors_origins = config.cors_origins.clone();
 
     HttpServer::new(move || {
         
         let json_config = web::JsonConfig::default()
-            .limit(4096)
+            .limit(json_limit)
             .error_handler(|err, _req| {
-                error::InternalError::from_response(err, HttpResponse::BadRequest().finish())
+                InternalError::from_response(err, HttpResponse::BadRequest().finish())
                 .into()
             });
 
-        let cors = Cors::default() // Allow requests from any origin
-            .allow_any_origin()
+        let mut cors = Cors::default()
             .allowed_methods(vec!["GET", "POST", "PUT", "DELETE"])
             .max_age(3600); // Cache preflight request for 1 hour
 
+        for origin in &cors_origins {
+            cors = if origin == "*" { cors.allow_any_origin() } else { cors.allowed_origin(origin) };
+        }
+
         App::new()
             .wrap(cors)
+            .app_data(registry.clone())
             .service(web::scope("/api/v1")
                 .app_data(json_config)
                 .configure(scoped_config))
     })
-    .workers(2)
-    .bind(("127.0.0.1", 8080))?
+    .workers(config.workers)
+    .bind((config.bind_address.as_str(), config.port))?
     .run()
     .await
 }
\ No newline at end of file
@@ -0,0 +1,394 @@
+pub mod network {
+
+    use std::collections::BTreeMap;
+    use futures::future::{try_join, try_join4};
+    use serde::{Deserialize, Serialize};
+
+    use crate::cluster_source::cluster_source::ClusterSource;
+    use crate::error::error::ReaderError;
+    use crate::kube_model::kube_model::{Endpoint, EndpointSlice, Ingress, IngressBackend, ObjectReference, Service, ServicePort};
+    use crate::use_case::use_case::ensure_namespace_exists;
+
+    // Label EndpointSlices carry the name of the service they belong to in
+    const SERVICE_NAME_LABEL: &str = "kubernetes.io/service-name";
+
+    #[derive(Debug)]
+    pub struct NamespaceNetwork {
+        namespace: String,
+        services: Vec<Service>,
+        endpoint_slices: Vec<EndpointSlice>,
+        ingresses: Vec<Ingress>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct ServiceDetails {
+        name: String,
+        namespace: String,
+        service_type: Option<String>,
+        cluster_ip: Option<String>,
+        external_ips: Vec<String>,
+        external_name: Option<String>,
+        load_balancer: Vec<String>,
+        ports: Vec<ServicePortDetails>,
+        selector: BTreeMap<String, String>,
+        endpoints: EndpointReadiness,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct ServicePortDetails {
+        name: Option<String>,
+        protocol: Option<String>,
+        port: i32,
+        // A port number or the name of a container port
+        target_port: Option<String>,
+        node_port: Option<i32>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct EndpointReadiness {
+        // Backends, usually pods, not addresses
+        ready: usize,
+        not_ready: usize,
+        addresses: Vec<EndpointDetails>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct EndpointDetails {
+        addresses: Vec<String>,
+        ready: bool,
+        terminating: bool,
+        node: Option<String>,
+        // kind/name of what serves the address, usually a pod
+        target: Option<String>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct Services {
+        services: Vec<ServiceDetails>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct IngressDetails {
+        name: String,
+        namespace: String,
+        class: Option<String>,
+        load_balancer: Vec<String>,
+        default_backend: Option<IngressBackendDetails>,
+        rules: Vec<IngressRuleDetails>,
+        tls: Vec<IngressTlsDetails>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct IngressRuleDetails {
+        // None matches every host
+        host: Option<String>,
+        paths: Vec<IngressPathDetails>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct IngressPathDetails {
+        path: Option<String>,
+        path_type: Option<String>,
+        backend: IngressBackendDetails,
+    }
+
+    // Either service and port or resource is set
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct IngressBackendDetails {
+        service: Option<String>,
+        port: Option<String>,
+        resource: Option<String>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct IngressTlsDetails {
+        hosts: Vec<String>,
+        secret_name: Option<String>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct Ingresses {
+        ingresses: Vec<IngressDetails>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct NetworkDetailsPerNamespace {
+        namespace: String,
+        services: Vec<ServiceDetails>,
+        ingresses: Vec<IngressDetails>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct TotalNetworkDetails {
+        total_details: Vec<NetworkDetailsPerNamespace>,
+    }
+
+/**********************************************************************************************************/
+
+    pub async fn get_network_details_handler(source: &dyn ClusterSource) -> Result<TotalNetworkDetails, ReaderError> {
+
+        // One list per kind across all namespaces rather than three per namespace
+        let (namespaces, services, endpoint_slices, ingresses) = try_join4(
+            source.list_namespaces(),
+            source.list_services(None),
+            source.list_endpoint_slices(None),
+            source.list_ingresses(None))
+            .await?;
+
+        let total_details = namespaces
+            .into_iter()
+            .map(|namespace| NamespaceNetwork {
+                services: in_namespace(&services, &namespace, |service| service.metadata.namespace.as_deref()),
+                endpoint_slices: in_namespace(&endpoint_slices, &namespace, |slice| slice.metadata.namespace.as_deref()),
+                ingresses: in_namespace(&ingresses, &namespace, |ingress| ingress.metadata.namespace.as_deref()),
+                namespace,
+            })
+            .map(|namespace_network| process_namespace_network(&namespace_network))
+            .collect();
+
+        Ok(TotalNetworkDetails { total_details })
+    }
+
+    fn in_namespace<T: Clone>(objects: &[T], namespace: &str, namespace_of: impl Fn(&T) -> Option<&str>) -> Vec<T> {
+        objects
+            .iter()
+            .filter(|object| namespace_of(object) == Some(namespace))
+            .cloned()
+            .collect()
+    }
+
+    pub async fn get_namespace_services_handler(source: &dyn ClusterSource, namespace: &str) -> Result<Services, ReaderError> {
+
+        ensure_namespace_exists(source, namespace).await?;
+
+        let (services, endpoint_slices) = try_join(source.list_services(Some(namespace)), source.list_endpoint_slices(Some(namespace))).await?;
+
+        Ok(Services {
+            services: process_services(&services, &endpoint_slices),
+        })
+    }
+
+    pub async fn get_namespace_ingresses_handler(source: &dyn ClusterSource, namespace: &str) -> Result<Ingresses, ReaderError> {
+
+        ensure_namespace_exists(source, namespace).await?;
+
+        let ingresses = source.list_ingresses(Some(namespace)).await?;
+
+        Ok(Ingresses {
+            ingresses: process_ingresses(&ingresses),
+        })
+    }
+
+    fn process_namespace_network(namespace_network: &NamespaceNetwork) -> NetworkDetailsPerNamespace {
+        NetworkDetailsPerNamespace {
+            namespace: String::from(&namespace_network.namespace),
+            services: process_services(&namespace_network.services, &namespace_network.endpoint_slices),
+            ingresses: process_ingresses(&namespace_network.ingresses),
+        }
+    }
+
+/**********************************************************************************************************/
+
+    // `endpoint_slices` are all slices in the services' namespace
+    fn process_services(services: &[Service], endpoint_slices: &[EndpointSlice]) -> Vec<ServiceDetails> {
+
+        let mut services: Vec<ServiceDetails> = services
+            .iter()
+            .map(|service| {
+                let service_slices: Vec<&EndpointSlice> = endpoint_slices
+                    .iter()
+                    .filter(|slice| slice.metadata.labels.get(SERVICE_NAME_LABEL) == Some(&service.metadata.name))
+                    .collect();
+
+                process_service(service, &service_slices)
+            })
+            .collect();
+
+        services.sort_by(|left, right| left.name.cmp(&right.name));
+
+        services
+    }
+
+    fn process_service(service: &Service, endpoint_slices: &[&EndpointSlice]) -> ServiceDetails {
+
+        let addresses: Vec<EndpointDetails> = endpoint_slices
+            .iter()
+            .flat_map(|slice| slice.endpoints.iter())
+            .map(process_endpoint)
+            .collect();
+
+        // A dual-stack service has one slice per address family listing the same pods, so readiness is
+        // counted per backend. A backend is ready when every slice says so.
+        let mut backends: BTreeMap<String, bool> = BTreeMap::new();
+        for endpoint in &addresses {
+            let backend = endpoint.target.clone().unwrap_or_else(|| endpoint.addresses.join(","));
+            *backends.entry(backend).or_insert(true) &= endpoint.ready;
+        }
+
+        let ready = backends.values().filter(|ready| **ready).count();
+
+        ServiceDetails {
+            name: String::from(&service.metadata.name),
+            namespace: service.metadata.namespace.clone().unwrap_or_default(),
+            service_type: service.spec.service_type.clone(),
+            cluster_ip: service.spec.cluster_ip.clone(),
+            external_ips: service.spec.external_ips.clone(),
+            external_name: service.spec.external_name.clone(),
+            load_balancer: service.status.load_balancer.addresses(),
+            ports: service.spec.ports.iter().map(process_service_port).collect(),
+            selector: service.spec.selector.clone(),
+            endpoints: EndpointReadiness {
+                ready,
+                not_ready: backends.len() - ready,
+                addresses,
+            },
+        }
+    }
+
+    fn process_service_port(port: &ServicePort) -> ServicePortDetails {
+        ServicePortDetails {
+            name: port.name.clone(),
+            protocol: port.protocol.clone(),
+            port: port.port,
+            target_port: port.target_port.as_ref().map(ToString::to_string),
+            node_port: port.node_port,
+        }
+    }
+
+    fn process_endpoint(endpoint: &Endpoint) -> EndpointDetails {
+        EndpointDetails {
+            addresses: endpoint.addresses.clone(),
+            ready: endpoint.conditions.ready.unwrap_or(true),
+            terminating: endpoint.conditions.terminating.unwrap_or(false),
+            node: endpoint.node_name.clone(),
+            target: endpoint.target_ref.as_ref().and_then(ObjectReference::kind_and_name),
+        }
+    }
+
+/**********************************************************************************************************/
+
+    fn process_ingresses(ingresses: &[Ingress]) -> Vec<IngressDetails> {
+
+        let mut ingresses: Vec<IngressDetails> = ingresses.iter().map(process_ingress).collect();
+        ingresses.sort_by(|left, right| left.name.cmp(&right.name));
+
+        ingresses
+    }
+
+    fn process_ingress(ingress: &Ingress) -> IngressDetails {
+        IngressDetails {
+            name: String::from(&ingress.metadata.name),
+            namespace: ingress.metadata.namespace.clone().unwrap_or_default(),
+            class: ingress.spec.ingress_class_name.clone(),
+            load_balancer: ingress.status.load_balancer.addresses(),
+            default_backend: ingress.spec.default_backend.as_ref().map(process_ingress_backend),
+            rules: ingress.spec.rules
+                .iter()
+                .map(|rule| IngressRuleDetails {
+                    host: rule.host.clone(),
+                    paths: rule.http
+                        .iter()
+                        .flat_map(|http| http.paths.iter())
+                        .map(|path| IngressPathDetails {
+                            path: path.path.clone(),
+                            path_type: path.path_type.clone(),
+                            backend: process_ingress_backend(&path.backend),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            tls: ingress.spec.tls
+                .iter()
+                .map(|tls| IngressTlsDetails {
+                    hosts: tls.hosts.clone(),
+                    secret_name: tls.secret_name.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    fn process_ingress_backend(backend: &IngressBackend) -> IngressBackendDetails {
+        IngressBackendDetails {
+            service: backend.service.as_ref().map(|service| String::from(&service.name)),
+            port: backend.service
+                .as_ref()
+                .and_then(|service| service.port.as_ref())
+                .and_then(|port| port.number.map(|number| number.to_string()).or_else(|| port.name.clone())),
+            resource: backend.resource.as_ref().and_then(ObjectReference::kind_and_name),
+        }
+    }
+
+/**********************************************************************************************************/
+
+    #[cfg(test)]
+    mod tests {
+
+        use serde_json::json;
+
+        use super::*;
+
+        fn endpoint_slice(family: &str, endpoints: serde_json::Value) -> EndpointSlice {
+            serde_json::from_value(json!({
+                "metadata": { "name": format!("web-{}", family), "labels": { SERVICE_NAME_LABEL: "web" } },
+                "addressType": family,
+                "endpoints": endpoints,
+            })).unwrap()
+        }
+
+        fn endpoint(address: &str, pod: &str, ready: bool) -> serde_json::Value {
+            json!({ "addresses": [address], "conditions": { "ready": ready }, "targetRef": { "kind": "Pod", "name": pod } })
+        }
+
+        #[test]
+        fn dual_stack_services_count_each_pod_once() {
+            let service: Service = serde_json::from_value(json!({ "metadata": { "name": "web" } })).unwrap();
+
+            let ipv4 = endpoint_slice("IPv4", json!([endpoint("10.0.0.1", "web-a", true), endpoint("10.0.0.2", "web-b", false)]));
+            let ipv6 = endpoint_slice("IPv6", json!([endpoint("fd00::1", "web-a", true), endpoint("fd00::2", "web-b", false)]));
+
+            let details = process_service(&service, &[&ipv4, &ipv6]);
+
+            assert_eq!(details.endpoints.ready, 1);
+            assert_eq!(details.endpoints.not_ready, 1);
+            assert_eq!(details.endpoints.addresses.len(), 4);
+        }
+
+        #[test]
+        fn endpoints_without_a_target_count_by_address() {
+            let service: Service = serde_json::from_value(json!({ "metadata": { "name": "web" } })).unwrap();
+
+            let slice = endpoint_slice("IPv4", json!([{ "addresses": ["192.168.1.10"] }, { "addresses": ["192.168.1.11"], "conditions": { "ready": false } }]));
+
+            let details = process_service(&service, &[&slice]);
+
+            assert_eq!(details.endpoints.ready, 1);
+            assert_eq!(details.endpoints.not_ready, 1);
+        }
+    }
+}
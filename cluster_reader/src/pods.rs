@@ -0,0 +1,148 @@
+pub mod pods {
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::cluster_source::cluster_source::ClusterSource;
+    use crate::error::error::ReaderError;
+    use crate::kube_model::kube_model::{ContainerState, ContainerStatus, ObjectMeta, Pod};
+    use crate::use_case::use_case::ensure_namespace_exists;
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct PodDetails {
+        name: String,
+        namespace: String,
+        phase: Option<String>,
+        node: Option<String>,
+        owner: Option<OwnerDetails>,
+        containers: Vec<ContainerStatusDetails>,
+        // Sum over all containers, init containers included
+        restart_count: i32,
+        start_time: Option<String>,
+        host_ip: Option<String>,
+        pod_ips: Vec<String>,
+    }
+
+    // The controller managing a pod or workload
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct OwnerDetails {
+        kind: String,
+        name: String,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct ContainerStatusDetails {
+        name: String,
+        init: bool,
+        ready: bool,
+        restart_count: i32,
+        // running, waiting or terminated
+        state: Option<String>,
+        reason: Option<String>,
+        exit_code: Option<i32>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct Pods {
+        pods: Vec<PodDetails>,
+    }
+
+    // Just enough to find a pod from the workload that runs it
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct PodReference {
+        name: String,
+        phase: Option<String>,
+        node: Option<String>,
+        ready: bool,
+    }
+
+    pub async fn get_pods_handler(source: &dyn ClusterSource) -> Result<Pods, ReaderError> {
+
+        let pods = source.list_pods(None).await?;
+
+        Ok(process_pods(&pods))
+    }
+
+    pub async fn get_namespace_pods_handler(source: &dyn ClusterSource, namespace: &str) -> Result<Pods, ReaderError> {
+
+        ensure_namespace_exists(source, namespace).await?;
+
+        let pods = source.list_pods(Some(namespace)).await?;
+
+        Ok(process_pods(&pods))
+    }
+
+    fn process_pods(pods: &[Pod]) -> Pods {
+
+        let mut pods: Vec<PodDetails> = pods.iter().map(process_pod).collect();
+        pods.sort_by(|left, right| (&left.namespace, &left.name).cmp(&(&right.namespace, &right.name)));
+
+        Pods { pods }
+    }
+
+    pub fn process_pod(pod: &Pod) -> PodDetails {
+
+        let init_containers = pod.status.init_container_statuses.iter().map(|status| process_container_status(status, true));
+        let containers: Vec<ContainerStatusDetails> = init_containers
+            .chain(pod.status.container_statuses.iter().map(|status| process_container_status(status, false)))
+            .collect();
+
+        PodDetails {
+            name: String::from(&pod.metadata.name),
+            namespace: pod.metadata.namespace.clone().unwrap_or_default(),
+            phase: pod.status.phase.clone(),
+            node: pod.spec.node_name.clone(),
+            owner: process_owner(&pod.metadata),
+            restart_count: containers.iter().map(|container| container.restart_count).sum(),
+            containers,
+            start_time: pod.status.start_time.clone(),
+            host_ip: pod.status.host_ip.clone(),
+            pod_ips: pod.status.pod_ips.iter().map(|pod_ip| String::from(&pod_ip.ip)).collect(),
+        }
+    }
+
+    pub fn process_owner(metadata: &ObjectMeta) -> Option<OwnerDetails> {
+        metadata.controller().map(|owner| OwnerDetails {
+            kind: String::from(&owner.kind),
+            name: String::from(&owner.name),
+        })
+    }
+
+    fn process_container_status(status: &ContainerStatus, init: bool) -> ContainerStatusDetails {
+
+        let (state, detail) = match &status.state {
+            ContainerState { running: Some(detail), .. } => (Some("running"), Some(detail)),
+            ContainerState { waiting: Some(detail), .. } => (Some("waiting"), Some(detail)),
+            ContainerState { terminated: Some(detail), .. } => (Some("terminated"), Some(detail)),
+            _ => (None, None),
+        };
+
+        ContainerStatusDetails {
+            name: String::from(&status.name),
+            init,
+            ready: status.ready,
+            restart_count: status.restart_count,
+            state: state.map(String::from),
+            reason: detail.and_then(|detail| detail.reason.clone()),
+            exit_code: detail.and_then(|detail| detail.exit_code),
+        }
+    }
+
+    pub fn pod_reference(pod: &Pod) -> PodReference {
+        PodReference {
+            name: String::from(&pod.metadata.name),
+            phase: pod.status.phase.clone(),
+            node: pod.spec.node_name.clone(),
+            ready: !pod.status.container_statuses.is_empty() && pod.status.container_statuses.iter().all(|status| status.ready),
+        }
+    }
+}
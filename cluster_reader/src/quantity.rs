@@ -0,0 +1,241 @@
+pub mod quantity {
+
+    use serde::{Deserialize, Serialize};
+
+    // A Kubernetes resource quantity such as "500m", "16Gi", "1.5G" or "12e6", kept as an exact
+    // decimal so normalizing never loses precision: value = digits * 10^exponent10 * 2^exponent2
+    #[derive(Debug)]
+    #[derive(Clone, Copy, PartialEq)]
+    pub struct Quantity {
+        digits: i128,
+        exponent10: i32,
+        exponent2: u32,
+    }
+
+    impl Quantity {
+
+        pub fn parse(input: &str) -> Option<Quantity> {
+
+            let input = input.trim();
+
+            let (negative, unsigned) = match input.strip_prefix('-') {
+                Some(rest) => (true, rest),
+                None => (false, input.strip_prefix('+').unwrap_or(input)),
+            };
+
+            let number_end = unsigned
+                .find(|character: char| !character.is_ascii_digit() && character != '.')
+                .unwrap_or(unsigned.len());
+            let (number, suffix) = unsigned.split_at(number_end);
+
+            let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+            if whole.is_empty() && fraction.is_empty() {
+                return None;
+            }
+
+            let (suffix_exponent10, exponent2) = parse_suffix(suffix)?;
+
+            let mut digits: i128 = 0;
+            for digit in whole.chars().chain(fraction.chars()) {
+                digits = digits.checked_mul(10)?.checked_add(i128::from(digit.to_digit(10)?))?;
+            }
+
+            Some(Quantity {
+                digits: if negative { -digits } else { digits },
+                exponent10: suffix_exponent10.checked_sub(i32::try_from(fraction.len()).ok()?)?,
+                exponent2,
+            })
+        }
+
+        // The value times 10^power10, rounded away from zero the way the apiserver rounds, or None if
+        // it does not fit an i64
+        fn scaled(&self, power10: i32) -> Option<i64> {
+
+            let mut value = self.digits.checked_mul(1i128.checked_shl(self.exponent2)?)?;
+            let exponent = self.exponent10.checked_add(power10)?;
+
+            if exponent >= 0 {
+                value = value.checked_mul(10i128.checked_pow(exponent.unsigned_abs())?)?;
+            } else {
+                // Anything past 10^38 rounds the value to -1, 0 or 1
+                let divisor = match 10i128.checked_pow(exponent.unsigned_abs()) {
+                    Some(divisor) => divisor,
+                    None => return i64::try_from(value.signum()).ok(),
+                };
+
+                let quotient = value / divisor;
+                value = if value % divisor != 0 { quotient + value.signum() } else { quotient };
+            }
+
+            i64::try_from(value).ok()
+        }
+
+        pub fn value(&self) -> Option<i64> {
+            self.scaled(0)
+        }
+
+        pub fn milli_value(&self) -> Option<i64> {
+            self.scaled(3)
+        }
+    }
+
+    fn parse_suffix(suffix: &str) -> Option<(i32, u32)> {
+
+        let exponents = match suffix {
+            "" => (0, 0),
+            "Ki" => (0, 10),
+            "Mi" => (0, 20),
+            "Gi" => (0, 30),
+            "Ti" => (0, 40),
+            "Pi" => (0, 50),
+            "Ei" => (0, 60),
+            "n" => (-9, 0),
+            "u" => (-6, 0),
+            "m" => (-3, 0),
+            "k" => (3, 0),
+            "M" => (6, 0),
+            "G" => (9, 0),
+            "T" => (12, 0),
+            "P" => (15, 0),
+            "E" => (18, 0),
+            // Exponent notation, "E" on its own is exa
+            _ => {
+                let exponent = suffix.strip_prefix(['e', 'E'])?;
+                (exponent.parse::<i32>().ok()?, 0)
+            }
+        };
+
+        Some(exponents)
+    }
+
+/**********************************************************************************************************/
+
+    // The numeric fields are null when the original can't be parsed or doesn't fit an i64
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct CpuQuantity {
+        pub original: String,
+        pub millicores: Option<i64>,
+    }
+
+    impl CpuQuantity {
+        pub fn parse(original: &str) -> CpuQuantity {
+            CpuQuantity {
+                original: String::from(original),
+                millicores: Quantity::parse(original).and_then(|quantity| quantity.milli_value()),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct ByteQuantity {
+        pub original: String,
+        pub bytes: Option<i64>,
+    }
+
+    impl ByteQuantity {
+        pub fn parse(original: &str) -> ByteQuantity {
+            ByteQuantity {
+                original: String::from(original),
+                bytes: Quantity::parse(original).and_then(|quantity| quantity.value()),
+            }
+        }
+    }
+
+    // Plain counts such as pods
+    #[derive(Debug)]
+    #[derive(Clone)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct CountQuantity {
+        pub original: String,
+        pub value: Option<i64>,
+    }
+
+    impl CountQuantity {
+        pub fn parse(original: &str) -> CountQuantity {
+            CountQuantity {
+                original: String::from(original),
+                value: Quantity::parse(original).and_then(|quantity| quantity.value()),
+            }
+        }
+    }
+
+/**********************************************************************************************************/
+
+    #[cfg(test)]
+    mod tests {
+
+        use super::*;
+
+        // (input, value, milli_value)
+        const CASES: &[(&str, Option<i64>, Option<i64>)] = &[
+            ("0", Some(0), Some(0)),
+            ("500m", Some(1), Some(500)),
+            ("2", Some(2), Some(2000)),
+            ("1.5", Some(2), Some(1500)),
+            ("16Gi", Some(17_179_869_184), Some(17_179_869_184_000)),
+            ("1.5Gi", Some(1_610_612_736), Some(1_610_612_736_000)),
+            ("128974848", Some(128_974_848), Some(128_974_848_000)),
+            ("129M", Some(129_000_000), Some(129_000_000_000)),
+            ("123Mi", Some(128_974_848), Some(128_974_848_000)),
+            ("12e6", Some(12_000_000), Some(12_000_000_000)),
+            ("1e3", Some(1000), Some(1_000_000)),
+            ("1E3", Some(1000), Some(1_000_000)),
+            ("1E", Some(1_000_000_000_000_000_000), None),
+            ("1Ei", Some(1_152_921_504_606_846_976), None),
+            ("1k", Some(1000), Some(1_000_000)),
+            ("1n", Some(1), Some(1)),
+            ("1u", Some(1), Some(1)),
+            ("1500u", Some(1), Some(2)),
+            (".5", Some(1), Some(500)),
+            ("+250m", Some(1), Some(250)),
+            ("-500m", Some(-1), Some(-500)),
+            ("-1n", Some(-1), Some(-1)),
+            ("-2Gi", Some(-2_147_483_648), Some(-2_147_483_648_000)),
+            ("1e-40", Some(1), Some(1)),
+            ("-1e-40", Some(-1), Some(-1)),
+            ("7Ei", Some(8_070_450_532_247_928_832), None),
+            ("8Ei", None, None),
+            ("9223372036854775807", Some(i64::MAX), None),
+            ("9223372036854775808", None, None),
+        ];
+
+        #[test]
+        fn parses_and_normalizes_quantities() {
+            for (input, value, milli_value) in CASES {
+                let quantity = Quantity::parse(input).unwrap_or_else(|| panic!("{} did not parse", input));
+
+                assert_eq!(quantity.value(), *value, "value of {}", input);
+                assert_eq!(quantity.milli_value(), *milli_value, "milli value of {}", input);
+            }
+        }
+
+        #[test]
+        fn rejects_malformed_quantities() {
+            for input in ["", "m", ".", "1.2.3", "1Ki2", "abc", "1x", "1e", "--1", "1 Gi", "1000000000000000000000000000000000000000000"] {
+                assert_eq!(Quantity::parse(input), None, "{}", input);
+            }
+        }
+
+        #[test]
+        fn keeps_the_original_next_to_the_normalized_value() {
+            let cpu = CpuQuantity::parse("250m");
+            assert_eq!((cpu.original.as_str(), cpu.millicores), ("250m", Some(250)));
+
+            let memory = ByteQuantity::parse("1Gi");
+            assert_eq!((memory.original.as_str(), memory.bytes), ("1Gi", Some(1_073_741_824)));
+
+            let invalid = ByteQuantity::parse("lots");
+            assert_eq!((invalid.original.as_str(), invalid.bytes), ("lots", None));
+
+            assert_eq!(CountQuantity::parse("110").value, Some(110));
+        }
+    }
+}
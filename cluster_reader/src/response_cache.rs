@@ -0,0 +1,248 @@
+pub mod response_cache {
+
+    use std::future::Future;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, RwLock};
+    use std::time::Duration;
+    use tokio::sync::Mutex;
+    use tokio::time::Instant;
+
+    use crate::error::error::ReaderError;
+
+    struct CacheEntry<T> {
+        value: Arc<T>,
+        fetched_at: Instant,
+    }
+
+    // Holds the last computed response for one route of one cluster. Entries younger than the ttl
+    // are served as is, entries up to twice the ttl old are served while a refresh runs in the
+    // background and anything older is fetched before responding. A ttl of zero disables caching.
+    pub struct ResponseCache<T> {
+        ttl: Duration,
+        entry: RwLock<Option<CacheEntry<T>>>,
+        // Bumped by invalidate so a fetch that started before it doesn't store an outdated value
+        generation: AtomicU64,
+        // Held while fetching so concurrent requests share one fetch instead of starting their own
+        refreshing: Arc<Mutex<()>>,
+    }
+
+    impl<T: Send + Sync + 'static> ResponseCache<T> {
+
+        pub fn new(ttl: Duration) -> ResponseCache<T> {
+            ResponseCache {
+                ttl,
+                entry: RwLock::new(None),
+                generation: AtomicU64::new(0),
+                refreshing: Arc::new(Mutex::new(())),
+            }
+        }
+
+        // `refresh` skips the cached value, used for ?refresh=true
+        pub async fn get<F, Fut>(self: &Arc<Self>, refresh: bool, fetch: F) -> Result<Arc<T>, ReaderError>
+        where
+            F: FnOnce() -> Fut,
+            Fut: Future<Output = Result<T, ReaderError>> + Send + 'static,
+        {
+            if self.ttl.is_zero() {
+                return fetch().await.map(Arc::new);
+            }
+
+            if !refresh {
+                if let Some((value, age)) = self.current() {
+                    if age < self.ttl {
+                        return Ok(value);
+                    }
+
+                    if age < self.ttl * 2 {
+                        self.refresh_in_background(fetch());
+                        return Ok(value);
+                    }
+                }
+            }
+
+            self.refresh(refresh, fetch()).await
+        }
+
+        fn current(&self) -> Option<(Arc<T>, Duration)> {
+            self.entry
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .as_ref()
+                .map(|entry| (entry.value.clone(), entry.fetched_at.elapsed()))
+        }
+
+        // Drops the cached value, the next request fetches again
+        pub fn invalidate(&self) {
+            let mut entry = self.entry.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+            self.generation.fetch_add(1, Ordering::AcqRel);
+            *entry = None;
+        }
+
+        fn store(&self, value: Arc<T>, fetched_at: Instant, generation: u64) {
+            let mut entry = self.entry.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if self.generation.load(Ordering::Acquire) == generation {
+                *entry = Some(CacheEntry { value, fetched_at });
+            }
+        }
+
+        async fn refresh<Fut>(&self, refresh: bool, fetch: Fut) -> Result<Arc<T>, ReaderError>
+        where
+            Fut: Future<Output = Result<T, ReaderError>>,
+        {
+            let requested_at = Instant::now();
+            let _refreshing = self.refreshing.lock().await;
+
+            // Another request fetched while this one waited for the lock. Its result will do unless this
+            // request asked for a refresh and that fetch started before the request came in.
+            if let Some(entry) = self.entry.read().unwrap_or_else(|poisoned| poisoned.into_inner()).as_ref() {
+                let new_enough = if refresh {
+                    entry.fetched_at >= requested_at
+                } else {
+                    entry.fetched_at.elapsed() < self.ttl
+                };
+
+                if new_enough {
+                    return Ok(entry.value.clone());
+                }
+            }
+
+            let fetched_at = Instant::now();
+            let generation = self.generation.load(Ordering::Acquire);
+            let value = Arc::new(fetch.await?);
+            self.store(value.clone(), fetched_at, generation);
+
+            Ok(value)
+        }
+
+        fn refresh_in_background<Fut>(self: &Arc<Self>, fetch: Fut)
+        where
+            Fut: Future<Output = Result<T, ReaderError>> + Send + 'static,
+        {
+            // A refresh is already running, its result will replace the stale entry
+            let Ok(refreshing) = self.refreshing.clone().try_lock_owned() else {
+                return;
+            };
+
+            let cache = self.clone();
+            let generation = self.generation.load(Ordering::Acquire);
+
+            tokio::spawn(async move {
+                let _refreshing = refreshing;
+                let fetched_at = Instant::now();
+
+                match fetch.await {
+                    Ok(value) => cache.store(Arc::new(value), fetched_at, generation),
+                    Err(err) => eprintln!("Background refresh failed: {}", err),
+                }
+            });
+        }
+    }
+
+/**********************************************************************************************************/
+
+    #[cfg(test)]
+    mod tests {
+
+        use std::sync::atomic::AtomicUsize;
+
+        use super::*;
+
+        const TTL: Duration = Duration::from_secs(10);
+
+        // Takes a second and returns how many fetches have started, itself included
+        async fn fetch(fetches: Arc<AtomicUsize>) -> Result<usize, ReaderError> {
+            let fetch = fetches.fetch_add(1, Ordering::SeqCst) + 1;
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok(fetch)
+        }
+
+        async fn get(cache: &Arc<ResponseCache<usize>>, refresh: bool, fetches: &Arc<AtomicUsize>) -> usize {
+            let fetches = fetches.clone();
+            *cache.get(refresh, move || fetch(fetches)).await.unwrap()
+        }
+
+        fn cache() -> (Arc<ResponseCache<usize>>, Arc<AtomicUsize>) {
+            (Arc::new(ResponseCache::new(TTL)), Arc::new(AtomicUsize::new(0)))
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn fresh_entries_are_served_from_the_cache() {
+            let (cache, fetches) = cache();
+
+            assert_eq!(get(&cache, false, &fetches).await, 1);
+
+            // The entry was stored when its fetch started, a second before the response
+            tokio::time::advance(Duration::from_secs(8)).await;
+            assert_eq!(get(&cache, false, &fetches).await, 1);
+            assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn stale_entries_are_served_while_one_background_fetch_runs() {
+            let (cache, fetches) = cache();
+
+            assert_eq!(get(&cache, false, &fetches).await, 1);
+            tokio::time::advance(Duration::from_secs(15)).await;
+
+            assert_eq!(get(&cache, false, &fetches).await, 1);
+            assert_eq!(get(&cache, false, &fetches).await, 1);
+
+            tokio::time::sleep(Duration::from_secs(2)).await;
+
+            assert_eq!(fetches.load(Ordering::SeqCst), 2);
+            assert_eq!(get(&cache, false, &fetches).await, 2);
+            assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn expired_entries_wait_for_a_new_fetch() {
+            let (cache, fetches) = cache();
+
+            assert_eq!(get(&cache, false, &fetches).await, 1);
+            tokio::time::advance(Duration::from_secs(25)).await;
+
+            let started = Instant::now();
+            assert_eq!(get(&cache, false, &fetches).await, 2);
+            assert_eq!(started.elapsed(), Duration::from_secs(1));
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn concurrent_refreshes_share_one_fetch() {
+            let (cache, fetches) = cache();
+
+            assert_eq!(get(&cache, false, &fetches).await, 1);
+
+            let (first, second) = tokio::join!(get(&cache, true, &fetches), get(&cache, true, &fetches));
+
+            assert_eq!((first, second), (2, 2));
+            assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+            // A refresh asked for after that fetch started gets a fetch of its own
+            assert_eq!(get(&cache, true, &fetches).await, 3);
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn invalidated_entries_are_fetched_again() {
+            let (cache, fetches) = cache();
+
+            assert_eq!(get(&cache, false, &fetches).await, 1);
+
+            cache.invalidate();
+            assert_eq!(get(&cache, false, &fetches).await, 2);
+
+            // A fetch that was running when the entry was invalidated isn't stored
+            let (fetched, ()) = tokio::join!(get(&cache, true, &fetches), async { cache.invalidate() });
+            assert_eq!(fetched, 3);
+            assert_eq!(get(&cache, false, &fetches).await, 4);
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn a_zero_ttl_fetches_every_time() {
+            let cache = Arc::new(ResponseCache::new(Duration::ZERO));
+            let fetches = Arc::new(AtomicUsize::new(0));
+
+            assert_eq!(get(&cache, false, &fetches).await, 1);
+            assert_eq!(get(&cache, false, &fetches).await, 2);
+        }
+    }
+}
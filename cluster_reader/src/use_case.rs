@@ -1,14 +1,20 @@
 pub mod use_case {
     
-    use std::io::{Error, BufReader, BufRead, ErrorKind};
-    use std::process::{Command, Stdio};
+    use std::collections::BTreeMap;
+    use futures::future::{try_join, try_join_all};
     use serde::{Deserialize, Serialize};
-    use regex::Regex;
 
-    pub async fn handle() -> Result<ClusterValues, Error> {
-        let z = get_node_list().await?;
-        let x = get_node_description(&z).await?;
-        let c = process_node_description(&x).await?;
+    use crate::allocation::allocation::{process_allocated_resources, AllocatedResources};
+    use crate::cluster_source::cluster_source::ClusterSource;
+    use crate::error::error::ReaderError;
+    use crate::kube_model::kube_model::{validate_name, Deployment, Node, NodeAddress, NodeCondition, NodeSystemInfo, Pod, PodSpec, ResourceRequirements, Taint};
+    use crate::pods::pods::{pod_reference, PodReference};
+    use crate::quantity::quantity::{ByteQuantity, CountQuantity, CpuQuantity};
+
+    pub async fn handle(source: &dyn ClusterSource) -> Result<ClusterValues, ReaderError> {
+        let z = get_node_list(source).await?;
+        let (x, pods) = try_join(get_nodes(source, &z), get_node_pods(source, &z)).await?;
+        let c = process_nodes(&x, &pods);
 
         Ok(c)
     }
@@ -20,87 +26,36 @@ pub mod use_case {
         node_name: Vec<String>
     }
 
-    pub async fn get_node_list() -> Result<NodeList, Error> {
-
-        let get_node_list_command = Command::new("kubectl")
-            .arg("get")
-            .arg("nodes")
-            .arg("--output=name")
-            .stdout(Stdio::piped())
-            .spawn()?;
-
-            let mut node_list = NodeList {
-                node_name: Vec::new()
-            };
-
-            if let Some(stdout) = get_node_list_command.stdout {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines() {
-                    let line = line?;
+    pub async fn get_node_list(source: &dyn ClusterSource) -> Result<NodeList, ReaderError> {
 
-                    node_list.node_name.push(line);
-                }
-            } else {
-                return Err(Error::new(ErrorKind::Other, "Failed to capture stdout in get_node_list"));
-            }
-        
-        node_list.node_name.sort();
+        let node_list = NodeList {
+            node_name: source.list_nodes().await?
+        };
 
         Ok(node_list)
     }
 
 /**********************************************************************************************************/
 
-    #[derive(Debug)]
-    #[derive(Deserialize)]
-    #[derive(Serialize)] 
-    pub struct NodesAndDescriptionList {
-        node_list: Vec<NodeAndDescription>,
-    }
+    pub async fn get_nodes(source: &dyn ClusterSource, node_list: &NodeList) -> Result<Vec<Node>, ReaderError> {
 
-    #[derive(Debug)]
-    #[derive(Deserialize)]
-    #[derive(Serialize)] 
-    struct NodeAndDescription {
-        node_name: String,
-        node_description: String
+        // Fetched concurrently, the source limits how many calls are in flight
+        try_join_all(node_list.node_name.iter().map(|node_name| source.get_node(node_name))).await
     }
 
-    pub async fn get_node_description(node_list: &NodeList) -> Result<NodesAndDescriptionList, Error> {
-
-        let mut node_and_description_list = NodesAndDescriptionList {
-            node_list: Vec::new()
-        };
-
-        for node in &node_list.node_name{
-            let get_node_description_command = Command::new("kubectl")
-                .arg("describe")
-                .arg(&node)
-                .stdout(Stdio::piped())
-                .spawn()?;
-
-                let mut node_and_description = NodeAndDescription {
-                    node_name: String::from(&node.to_string()), 
-                    node_description: String::new()
-                };
-
-                if let Some(stdout) = get_node_description_command.stdout {
-                    let reader = BufReader::new(stdout);
-                    for line in reader.lines() {
-                        let line = line?;
-    
-                        node_and_description.node_description.push_str(&line);
-                        node_and_description.node_description.push('\n');
-                        
-                    }
-                } else {
-                    return Err(Error::new(ErrorKind::Other, "Failed to capture stdout from node description"));
-                }
+    // The pods on each node, in the order of the node list
+    pub async fn get_node_pods(source: &dyn ClusterSource, node_list: &NodeList) -> Result<Vec<Option<Vec<Pod>>>, ReaderError> {
+        try_join_all(node_list.node_name.iter().map(|node_name| node_pods(source, node_name))).await
+    }
 
-                node_and_description_list.node_list.push(node_and_description);
+    // Allocated resources are left out rather than failing the request for a reader whose RBAC only
+    // covers nodes, any other failure to list pods fails the request
+    async fn node_pods(source: &dyn ClusterSource, node_name: &str) -> Result<Option<Vec<Pod>>, ReaderError> {
+        match source.list_node_pods(node_name).await {
+            Ok(pods) => Ok(Some(pods)),
+            Err(ReaderError::Forbidden { .. }) => Ok(None),
+            Err(err) => Err(err),
         }
-
-        Ok(node_and_description_list)
     }
 
 /**********************************************************************************************************/
@@ -119,193 +74,375 @@ pub mod use_case {
         annotations: Vec<String>
     }
 
+    // Node capacity or allocatable, null where the node doesn't report the resource
     #[derive(Debug)]
     #[derive(Deserialize)]
     #[derive(Serialize)] 
-    struct Capacity {
-        cpu: i32,
-        ephemeral_storage_ki: i32,
-        hugepages_2mi: i32,
-        memory_ki: i32,
-        pods: i32,
-    }
-
-    #[derive(Debug)]
-    #[derive(Deserialize)]
-    #[derive(Serialize)] 
-    struct Allocatable {
-        cpu: i32,
-        ephemeral_storage_bytes: i64,
-        hugepages_2mi: i32,
-        memory_ki: i32,
-        pods: i32,
+    struct NodeResources {
+        cpu: Option<CpuQuantity>,
+        ephemeral_storage: Option<ByteQuantity>,
+        hugepages_2mi: Option<ByteQuantity>,
+        memory: Option<ByteQuantity>,
+        pods: Option<CountQuantity>,
     }
 
     #[derive(Debug)]
     #[derive(Deserialize)]
     #[derive(Serialize)] 
-    struct FinalNodeValues {
+    pub struct FinalNodeValues {
         name: String,
         roles: String,
         labels: Labels,
         annotations: Annotations,
         created_date: String,
-        capacity: Capacity,
-        allocatable: Allocatable
+        capacity: NodeResources,
+        allocatable: NodeResources,
+        ready: bool,
+        conditions: Vec<NodeConditionDetails>,
+        // Cordoned
+        unschedulable: bool,
+        taints: Vec<TaintDetails>,
+        schedulable: bool,
+        system_info: SystemInfoDetails,
+        addresses: Vec<NodeAddressDetails>,
+        pod_cidrs: Vec<String>,
+        provider_id: Option<String>,
+        // Left out of change events, which don't have the node's pods, and when the pods can't be listed
+        #[serde(skip_serializing_if = "Option::is_none")]
+        allocated: Option<AllocatedResources>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    struct NodeAddressDetails {
+        #[serde(rename = "type")]
+        address_type: String,
+        address: String,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    struct SystemInfoDetails {
+        machine_id: String,
+        os_image: String,
+        kernel_version: String,
+        operating_system: String,
+        architecture: String,
+        // e.g. containerd://1.7.2
+        container_runtime_version: String,
+        kubelet_version: String,
+        kube_proxy_version: String,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    struct TaintDetails {
+        key: String,
+        value: Option<String>,
+        effect: String,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    struct NodeConditionDetails {
+        // Ready, MemoryPressure, DiskPressure, PIDPressure, NetworkUnavailable or one a plugin adds
+        #[serde(rename = "type")]
+        condition_type: String,
+        status: String,
+        reason: Option<String>,
+        message: Option<String>,
+        last_transition_time: Option<String>,
+    }
+
+    // Nodes whose Ready condition is missing count as unknown
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    struct ClusterReadiness {
+        ready: bool,
+        total_nodes: usize,
+        ready_nodes: usize,
+        not_ready_nodes: usize,
+        unknown_nodes: usize,
+        // Condition type to the nodes it is True on, for every condition but Ready
+        problems: BTreeMap<String, Vec<String>>,
     }
 
     #[derive(Debug)]
     #[derive(Deserialize)]
     #[derive(Serialize)] 
     pub struct ClusterValues {
+        readiness: ClusterReadiness,
         nodes: Vec<FinalNodeValues>
     }
 
-    pub async fn process_node_description(node_and_description_list: &NodesAndDescriptionList) -> Result<ClusterValues, Error> {
+    // /cluster-info?schedulable=true, the readiness summary still covers every node
+    #[derive(Debug)]
+    #[derive(Serialize)]
+    pub struct SchedulableNodes<'a> {
+        readiness: &'a ClusterReadiness,
+        nodes: Vec<&'a FinalNodeValues>,
+    }
 
-        const REGEX_PATTERN: &str = r"(^Name\:\s+)(.*)\n(Roles\:\s+)(.*)\n(Labels\:\s+)((.*\n)+)(Annotations\:\s+)((.*\n)+)(CreationTimestamp\:\s+)(.*)((.*\n)+)(Capacity\:)((.*\n)+)(Allocatable\:)((.*\n)+)(System Info\:\n)";
+    impl ClusterValues {
+        pub fn schedulable(&self) -> SchedulableNodes<'_> {
+            SchedulableNodes {
+                readiness: &self.readiness,
+                nodes: self.nodes.iter().filter(|node| node.schedulable).collect(),
+            }
+        }
+    }
 
-        let regex_pattern = Regex::new(REGEX_PATTERN).unwrap();
+    // `pods` are the pods on each node, in the same order as the nodes
+    pub fn process_nodes(nodes: &[Node], pods: &[Option<Vec<Pod>>]) -> ClusterValues {
 
         let mut clutser_values = ClusterValues {
+            readiness: process_cluster_readiness(nodes),
             nodes: Vec::new()
         };
 
-        for node in &node_and_description_list.node_list{
-            if let Some(captures) = regex_pattern.captures(&node.node_description) {
-                if let (
-                    Some(node_name_value),
-                    Some(node_role_value),
-                    Some(node_labels_value),
-                    Some(node_annotations_value),
-                    Some(node_created_value),
-                    Some(node_capacity_value),
-                    Some(node_allocatable_value)
-                ) = (
-                    captures.get(2),
-                    captures.get(4),
-                    captures.get(6),
-                    captures.get(9),
-                    captures.get(12),
-                    captures.get(16),
-                    captures.get(19)
-                ) {
-                    let get_labels = process_labels_string_into_array(&node_labels_value.as_str())?;
-        
-                    let get_annotations = process_annotations_string_into_vector(&node_annotations_value.as_str())?;
-                
-                    let get_capacity = process_capacity_into_value(&node_capacity_value.as_str())?;
-        
-                    let get_allocatable = process_allocatable_into_value(&node_allocatable_value.as_str())?;
-        
-                    let final_values = FinalNodeValues {
-                        name: String::from(node_name_value.as_str().trim()),
-                        roles: String::from(node_role_value.as_str().trim()),
-                        created_date: String::from(node_created_value.as_str().trim()),
-                        annotations: get_annotations,
-                        labels: get_labels,
-                        capacity: get_capacity,
-                        allocatable: get_allocatable
-                    };
-        
-                    clutser_values.nodes.push(final_values);
+        for (node, pods) in nodes.iter().zip(pods) {
+            clutser_values.nodes.push(process_node(node, pods.as_deref()));
+        }
+
+        clutser_values
+    }
+
+    fn process_cluster_readiness(nodes: &[Node]) -> ClusterReadiness {
+
+        let ready_status = |status: &str| nodes
+            .iter()
+            .filter(|node| node.condition("Ready").map(|condition| condition.status.as_str()).unwrap_or("Unknown") == status)
+            .count();
+
+        let mut problems: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for node in nodes {
+            for condition in &node.status.conditions {
+                if condition.condition_type != "Ready" && condition.status == "True" {
+                    problems.entry(String::from(&condition.condition_type)).or_default().push(String::from(&node.metadata.name));
                 }
             }
         }
 
-        Ok(clutser_values)
+        let ready_nodes = ready_status("True");
+
+        ClusterReadiness {
+            ready: !nodes.is_empty() && ready_nodes == nodes.len() && problems.is_empty(),
+            total_nodes: nodes.len(),
+            ready_nodes,
+            not_ready_nodes: ready_status("False"),
+            unknown_nodes: ready_status("Unknown"),
+            problems,
+        }
+    }
+
+    // `pods` are the pods scheduled on the node, they make up its allocated resources
+    pub fn process_node(node: &Node, pods: Option<&[Pod]>) -> FinalNodeValues {
+        FinalNodeValues {
+            name: String::from(&node.metadata.name),
+            roles: node.roles(),
+            created_date: node.metadata.creation_timestamp.clone().unwrap_or_default(),
+            annotations: process_annotations_into_vector(&node.metadata.annotations),
+            labels: process_labels_into_array(&node.metadata.labels),
+            capacity: process_node_resources(&node.status.capacity),
+            allocatable: process_node_resources(&node.status.allocatable),
+            ready: node.is_ready(),
+            conditions: node.status.conditions.iter().map(process_node_condition).collect(),
+            unschedulable: node.spec.unschedulable,
+            taints: node.spec.taints.iter().map(process_taint).collect(),
+            schedulable: node.is_schedulable(),
+            system_info: process_system_info(&node.status.node_info),
+            addresses: node.status.addresses.iter().map(process_node_address).collect(),
+            pod_cidrs: node.pod_cidrs(),
+            provider_id: node.spec.provider_id.clone(),
+            allocated: pods.map(|pods| process_allocated_resources(node, pods)),
+        }
+    }
+
+    fn process_node_address(address: &NodeAddress) -> NodeAddressDetails {
+        NodeAddressDetails {
+            address_type: String::from(&address.address_type),
+            address: String::from(&address.address),
+        }
+    }
+
+    fn process_system_info(node_info: &NodeSystemInfo) -> SystemInfoDetails {
+        SystemInfoDetails {
+            machine_id: String::from(&node_info.machine_id),
+            os_image: String::from(&node_info.os_image),
+            kernel_version: String::from(&node_info.kernel_version),
+            operating_system: String::from(&node_info.operating_system),
+            architecture: String::from(&node_info.architecture),
+            container_runtime_version: String::from(&node_info.container_runtime_version),
+            kubelet_version: String::from(&node_info.kubelet_version),
+            kube_proxy_version: String::from(&node_info.kube_proxy_version),
+        }
+    }
+
+    fn process_taint(taint: &Taint) -> TaintDetails {
+        TaintDetails {
+            key: String::from(&taint.key),
+            value: taint.value.clone(),
+            effect: String::from(&taint.effect),
+        }
+    }
+
+    fn process_node_condition(condition: &NodeCondition) -> NodeConditionDetails {
+        NodeConditionDetails {
+            condition_type: String::from(&condition.condition_type),
+            status: String::from(&condition.status),
+            reason: condition.reason.clone(),
+            message: condition.message.clone(),
+            last_transition_time: condition.last_transition_time.clone(),
+        }
     }
 
-    fn process_labels_string_into_array(input: &str) -> Result<Labels, Error>{
+    fn process_labels_into_array(input: &BTreeMap<String, String>) -> Labels {
 
         let mut labels_vector = Labels {
             labels: Vec::new(),
         };
     
-        for line in input.lines() {
-            labels_vector.labels.push(String::from(line.trim())); 
+        for (key, value) in input {
+            labels_vector.labels.push(format!("{}={}", key, value)); 
         }
     
-        Ok(labels_vector)    
+        labels_vector
     }
 
-    fn process_annotations_string_into_vector(input: &str) -> Result<Annotations, Error> {
+    fn process_annotations_into_vector(input: &BTreeMap<String, String>) -> Annotations {
         let mut annotations_vector = Annotations {
             annotations: Vec::new()
         };
     
-        for line in input.lines() {
-            
-            annotations_vector.annotations.push(String::from(line.trim()));
+        for (key, value) in input {
+            annotations_vector.annotations.push(format!("{}: {}", key, value));
         }
     
-        Ok(annotations_vector)
-    }
-
-    fn process_capacity_into_value(input: &str) -> Result<Capacity, Error> {
-
-        // Initialize the struct with default values
-        let mut resource = Capacity {
-           cpu: 0,
-           ephemeral_storage_ki: 0,
-           hugepages_2mi: 0,
-           memory_ki: 0,
-           pods: 0,
-       };
-   
-       // Splitting the multiline string into separate lines
-       for line in input.lines() {
-           // Split each line into key and value
-           let parts: Vec<&str> = line.split(':').map(|s| s.trim()).collect();
-           if parts.len() == 2 {
-               let key = parts[0];
-               let value = parts[1].trim().trim_end_matches("Ki").parse::<i32>().unwrap_or(0); // Parsing the value as integer
-               match key {
-                   "cpu" => resource.cpu = value,
-                   "ephemeral-storage" => resource.ephemeral_storage_ki = value,
-                   "hugepages-2Mi" => resource.hugepages_2mi = value,
-                   "memory" => resource.memory_ki = value,
-                   "pods" => resource.pods = value,
-                   _ => {}
-               }
-           }
-       }
-   
-       Ok(resource)
-   }
-
-    fn process_allocatable_into_value(input: &str) -> Result<Allocatable, Error>{
-
-        // Initialize the struct with default values
-        let mut resource = Allocatable {
-            cpu: 0,
-            ephemeral_storage_bytes: 0,
-            hugepages_2mi: 0,
-            memory_ki: 0,
-            pods: 0,
-        };
+        annotations_vector
+    }
+
+    fn process_node_resources(input: &BTreeMap<String, String>) -> NodeResources {
+        NodeResources {
+            cpu: input.get("cpu").map(|value| CpuQuantity::parse(value)),
+            ephemeral_storage: input.get("ephemeral-storage").map(|value| ByteQuantity::parse(value)),
+            hugepages_2mi: input.get("hugepages-2Mi").map(|value| ByteQuantity::parse(value)),
+            memory: input.get("memory").map(|value| ByteQuantity::parse(value)),
+            pods: input.get("pods").map(|value| CountQuantity::parse(value)),
+        }
+    }
+
+    // One line per node for /nodes, the full values are on /nodes/{name}
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct NodeSummary {
+        name: String,
+        roles: String,
+        ready: bool,
+        schedulable: bool,
+        addresses: Vec<NodeAddressDetails>,
+        pod_cidrs: Vec<String>,
+        provider_id: Option<String>,
+        capacity: NodeResources,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct NodeSummaries {
+        nodes: Vec<NodeSummary>,
+    }
+
+    // `search` keeps the nodes whose name, addresses, pod CIDRs or provider ID contain it, ignoring case
+    pub async fn get_node_summaries_handler(source: &dyn ClusterSource, search: Option<&str>) -> Result<NodeSummaries, ReaderError> {
+
+        let node_list = get_node_list(source).await?;
+        let nodes = get_nodes(source, &node_list).await?;
+
+        let search = search.map(str::to_lowercase);
+
+        Ok(NodeSummaries {
+            nodes: nodes
+                .iter()
+                .filter(|node| search.as_deref().is_none_or(|search| node_matches(node, search)))
+                .map(|node| NodeSummary {
+                    name: String::from(&node.metadata.name),
+                    roles: node.roles(),
+                    ready: node.is_ready(),
+                    schedulable: node.is_schedulable(),
+                    addresses: node.status.addresses.iter().map(process_node_address).collect(),
+                    pod_cidrs: node.pod_cidrs(),
+                    provider_id: node.spec.provider_id.clone(),
+                    capacity: process_node_resources(&node.status.capacity),
+                })
+                .collect(),
+        })
+    }
+
+    fn node_matches(node: &Node, search: &str) -> bool {
 
-        // Splitting the multiline string into separate lines
-        for line in input.lines() {
-            // Split each line into key and value
-            let parts: Vec<&str> = line.split(':').map(|s| s.trim()).collect();
-            if parts.len() == 2 {
-                let key = parts[0];
-                let value = parts[1].trim().trim_end_matches("Ki").parse::<i32>().unwrap_or(0); // Parsing the value as integer
-                match key {
-                    "cpu" => resource.cpu = value,
-                    "ephemeral-storage" => resource.ephemeral_storage_bytes = parts[1].trim().parse::<i64>().unwrap_or(0),
-                    "hugepages-2Mi" => resource.hugepages_2mi = value,
-                    "memory" => resource.memory_ki = value,
-                    "pods" => resource.pods = value,
-                    _ => {}
+        let mut fields = vec![node.metadata.name.as_str()];
+        fields.extend(node.status.addresses.iter().map(|address| address.address.as_str()));
+        fields.extend(node.spec.pod_cidrs.iter().map(String::as_str));
+        fields.extend(node.spec.pod_cidr.as_deref());
+        fields.extend(node.spec.provider_id.as_deref());
+
+        fields.iter().any(|field| field.to_lowercase().contains(search))
+    }
+
+    // Which nodes run which version of each component, for tracking upgrades. Each map goes from
+    // a version to the nodes on it, nodes that don't report a component are left out of its map.
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct VersionInventory {
+        kubelet: BTreeMap<String, Vec<String>>,
+        kube_proxy: BTreeMap<String, Vec<String>>,
+        container_runtime: BTreeMap<String, Vec<String>>,
+        os_image: BTreeMap<String, Vec<String>>,
+        kernel: BTreeMap<String, Vec<String>>,
+        architecture: BTreeMap<String, Vec<String>>,
+    }
+
+    pub async fn get_version_inventory_handler(source: &dyn ClusterSource) -> Result<VersionInventory, ReaderError> {
+
+        let node_list = get_node_list(source).await?;
+        let nodes = get_nodes(source, &node_list).await?;
+
+        let group_by = |version: fn(&NodeSystemInfo) -> &str| {
+            let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for node in &nodes {
+                let version = version(&node.status.node_info);
+                if !version.is_empty() {
+                    groups.entry(String::from(version)).or_default().push(String::from(&node.metadata.name));
                 }
             }
-        }
+            groups
+        };
 
-        Ok(resource)
+        Ok(VersionInventory {
+            kubelet: group_by(|node_info| &node_info.kubelet_version),
+            kube_proxy: group_by(|node_info| &node_info.kube_proxy_version),
+            container_runtime: group_by(|node_info| &node_info.container_runtime_version),
+            os_image: group_by(|node_info| &node_info.os_image),
+            kernel: group_by(|node_info| &node_info.kernel_version),
+            architecture: group_by(|node_info| &node_info.architecture),
+        })
     }
 
+    pub async fn get_node_handler(source: &dyn ClusterSource, node_name: &str) -> Result<FinalNodeValues, ReaderError> {
+
+        validate_name("node", node_name)?;
+
+        let (node, pods) = try_join(source.get_node(node_name), node_pods(source, node_name)).await?;
+
+        Ok(process_node(&node, pods.as_deref()))
+    }
 
 /**********************************************************************************************************/
 /**********************************************************************************************************/
@@ -313,11 +450,11 @@ pub mod use_case {
 /**********************************************************************************************************/
 /**********************************************************************************************************/
 
-    pub async fn get_namespace_details_handler() -> Result<TotalDeploymentDetails, Error> {
+    pub async fn get_namespace_details_handler(source: &dyn ClusterSource) -> Result<TotalDeploymentDetails, ReaderError> {
         
-        let z = get_namespaces().await?;
+        let z = get_namespaces(source).await?;
         
-        let x = get_deployments_and_details_by_namespace(&z).await?;
+        let x = get_deployments_and_details_by_namespace(source, &z).await?;
 
         let c = process_deployment_details_handler(&x).await?;
 
@@ -332,38 +469,12 @@ pub mod use_case {
         namespaces: Vec<String>
     }
 
-    pub async fn get_namespaces() -> Result<Namespaces, Error> {
+    pub async fn get_namespaces(source: &dyn ClusterSource) -> Result<Namespaces, ReaderError> {
 
-        let mut namespaces = Namespaces {
-            namespaces: Vec::new()
+        let namespaces = Namespaces {
+            namespaces: source.list_namespaces().await?
         };
 
-        let get_namespaces_command = Command::new("kubectl")
-            .arg("get")
-            .arg("namespaces")
-            .arg("--output=name")
-            .stdout(Stdio::piped())
-            .spawn()?;
-
-        if let Some(stdout) = get_namespaces_command.stdout {
-            let reader = BufReader::new(stdout);
-            for line in reader.lines() {
-                let line = line?;
-
-                let parts: Vec<&str> = line.split('/').collect();
-                
-                if let Some(item_two) = parts.get(1) {
-                    namespaces.namespaces.push(String::from(item_two.to_string()));
-                } else {
-                    println!("The string doesn't have a second part.");
-                }
-            }
-        } else {
-            return Err(Error::new(ErrorKind::Other, "Failed to capture stdout from get namespace command"));
-        }
-
-        namespaces.namespaces.sort();
-
         Ok(namespaces)
     }
 
@@ -371,107 +482,64 @@ pub mod use_case {
 /**********************************************************************************************************/
 
     #[derive(Debug)]
-    #[derive(Deserialize)]
-    #[derive(Serialize)] 
     pub struct DeploymentDetails {
         deployment: String, 
-        details: String
+        details: Deployment
     }
 
     #[derive(Debug)]
-    #[derive(Deserialize)]
-    #[derive(Serialize)] 
     pub struct NamespaceDetails {
         namespace: String,
-        deployment_details: Vec<DeploymentDetails>
+        deployment_details: Vec<DeploymentDetails>,
+        pods: Vec<Pod>,
     }
 
     #[derive(Debug)]
-    #[derive(Deserialize)]
-    #[derive(Serialize)] 
     pub struct AllNamespaceDetails {
         all_namespace_details: Vec<NamespaceDetails>
     }
 
-    pub async fn get_deployments_and_details_by_namespace(namespaces_list: &Namespaces) -> Result<AllNamespaceDetails, Error> {
+    pub async fn get_deployments_and_details_by_namespace(source: &dyn ClusterSource, namespaces_list: &Namespaces) -> Result<AllNamespaceDetails, ReaderError> {
         
-        let mut all_namespace_details = AllNamespaceDetails {
-            all_namespace_details: Vec::new()
+        // Namespaces and the deployments in them are fetched concurrently, the source limits
+        // how many calls are in flight
+        let all_namespace_details = AllNamespaceDetails {
+            all_namespace_details: try_join_all(namespaces_list.namespaces
+                .iter()
+                .map(|namespace| get_namespace_deployments(source, namespace)))
+                .await?
         };
-
-        for namespace in &namespaces_list.namespaces{
-            
-            let mut namespace_details = NamespaceDetails {
-                namespace: String::from(namespace),
-                deployment_details: Vec::new(),
-            };
-
-            // Get kubectl get deployments -n default --output=name
-            let get_deployments_by_namespace_command = Command::new("kubectl")
-                .arg("get")
-                .arg("deployments")
-                .arg("-n")
-                .arg(&namespace)
-                .arg("--output=name")
-                .stdout(Stdio::piped())
-                .spawn()?;
-
-            if let Some(stdout) = get_deployments_by_namespace_command.stdout {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines() {
-                    let line = line?;
-    
-                    let mut depolyment_details = DeploymentDetails {
-                        deployment: String::new(),
-                        details: String::new(),
-                    };
-
-                    let parts: Vec<&str> = line.split('/').collect();
-                
-                    let mut deployment_name = String::new();
-
-                    if let Some(item_two) = parts.get(1) {
-                        depolyment_details.deployment.push_str(&item_two);
-                        deployment_name = item_two.to_string(); 
-                    } else {
-                        println!("The string doesn't have a second part.");
-                    }
-
-                    // kubectl get deployment coredns -n kube-system -o yaml
-                    let get_deployment_details_command = Command::new("kubectl")
-                        .arg("get")
-                        .arg("deployment")
-                        .arg(&deployment_name)
-                        .arg("-n")
-                        .arg(&namespace)
-                        .arg("-o")
-                        .arg("yaml")
-                        .stdout(Stdio::piped())
-                        .spawn()?;
-
-                    if let Some(stdout) = get_deployment_details_command.stdout {
-                        let reader = BufReader::new(stdout);
-                        for line in reader.lines() {
-                            let line = line?;
-        
-                            depolyment_details.details.push_str(&line);
-                            depolyment_details.details.push('\n');
-                            
-                        }
-                    } else {
-                        return Err(Error::new(ErrorKind::Other, "Failed to capture stdout from node description"));
-                    }
-                    namespace_details.deployment_details.push(depolyment_details);
-                }
-            } else {
-                return Err(Error::new(ErrorKind::Other, "Failed to capture stdout from get namespace command"));
-            }
-            all_namespace_details.all_namespace_details.push(namespace_details);
-        }
         
         Ok(all_namespace_details)
     }
 
+    async fn get_namespace_deployments(source: &dyn ClusterSource, namespace: &str) -> Result<NamespaceDetails, ReaderError> {
+
+        let deployment_details = async {
+            let deployment_names = source.list_deployments(namespace).await?;
+
+            try_join_all(deployment_names
+                .into_iter()
+                .map(|deployment_name| async move {
+                    let details = source.get_deployment(namespace, &deployment_name).await?;
+
+                    Ok::<DeploymentDetails, ReaderError>(DeploymentDetails {
+                        deployment: deployment_name,
+                        details,
+                    })
+                }))
+                .await
+        };
+
+        let (deployment_details, pods) = try_join(deployment_details, source.list_pods(Some(namespace))).await?;
+
+        Ok(NamespaceDetails {
+            namespace: String::from(namespace),
+            deployment_details,
+            pods,
+        })
+    }
+
 
     #[derive(Debug)]
     #[derive(Deserialize)]
@@ -479,10 +547,12 @@ pub mod use_case {
     pub struct DeploymentDetailGroups {
         deployment_name: String,
         api_version: String,
-        replicas: String,
-        image: String,
+        replicas: Option<i32>,
         kind: String,
-        resources: Resources,
+        containers: Vec<ContainerDetails>,
+        // Pods picked by the deployment's selector that haven't finished, left out of change events
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pods: Option<Vec<PodReference>>,
     }
 
     #[derive(Debug)]
@@ -500,231 +570,177 @@ pub mod use_case {
         total_details: Vec<DeploymentDetailsPerNamespace>,
     }
 
-    pub async fn process_deployment_details_handler(deployment_details: &AllNamespaceDetails) -> Result<TotalDeploymentDetails, Error> {
-        
+    pub async fn process_deployment_details_handler(deployment_details: &AllNamespaceDetails) -> Result<TotalDeploymentDetails, ReaderError> {
+
         let mut total_deployment_details = TotalDeploymentDetails {
             total_details: Vec::new()
         };
 
         for namespace_details in &deployment_details.all_namespace_details {
-            
-            let mut depolyment_details_per_namespace = DeploymentDetailsPerNamespace {
-                namespace: String::from(&namespace_details.namespace),
-                deployment_details: Vec::new()
-            };
-
-            for item in &namespace_details.deployment_details {
-                
-                let mut depoloyment_detail_groups = DeploymentDetailGroups {
-                    deployment_name: String::from(&item.deployment),
-                    api_version: String::new(),
-                    replicas: String::new(),
-                    image: String::new(),
-                    kind: String::new(),
-                    resources: Resources {
-                        limits: ResourceLimits {
-                            cpu: String::new(),
-                            memory: String::new(),
-                        },
-                        requsts: ResoruceRequests {
-                            cpu: String::new(),
-                            memory: String::new(),
-                        }
-                    },
-                };
-
-                const REGEX_PATTERN: &str = r"(^apiVersion\:\s)(.*)\n(kind\:\s)(Deployment)\n(metadata\:)((.*\n)+)(spec\:)((.*\n)+)(status\:)((.*\n)+.*)";
-                let regex_pattern = Regex::new(REGEX_PATTERN).unwrap();
-
-                if let Some(captures) = regex_pattern.captures(&item.details) {
-                    if let (
-                        Some(deployment_api_version),
-                        Some(deployment_kind),
-                        Some(deployment_spec),
-                    ) = (
-                        captures.get(2),
-                        captures.get(4),
-                        captures.get(9),
-                    ) {
-                        depoloyment_detail_groups.api_version.push_str(deployment_api_version.as_str());
-                        depoloyment_detail_groups.kind.push_str(deployment_kind.as_str());
-
-                        let replica_result = get_replicas_from_spec(String::from(deployment_spec.as_str()))?;
-                        depoloyment_detail_groups.replicas.push_str(&replica_result);
-
-                        let image_result = get_image_from_spec(String::from(deployment_spec.as_str()))?;
-                        depoloyment_detail_groups.image.push_str(&image_result);
-
-                        let resources_result = get_resources_from_spec(String::from(deployment_spec.as_str()))?;
-                        depoloyment_detail_groups.resources = resources_result;    
-                    }
-                }
-
-                depolyment_details_per_namespace.deployment_details.push(depoloyment_detail_groups);
-            }
-
-            total_deployment_details.total_details.push(depolyment_details_per_namespace);
+            total_deployment_details.total_details.push(process_namespace_details(namespace_details));
         }
         Ok(total_deployment_details)
     }
 
-    pub fn get_replicas_from_spec(spec_string: String) -> Result<String, Error> {
+    fn process_namespace_details(namespace_details: &NamespaceDetails) -> DeploymentDetailsPerNamespace {
 
-        const REGEX_PATTERN: &str = r"(replicas\:\s)(\d+)";
-        let regex_pattern = Regex::new(REGEX_PATTERN).unwrap();
-        
-        if let Some(captures) = regex_pattern.captures(&spec_string) {
-            if let (
-                Some(spec_replicas),
-            ) = (
-                captures.get(2),
-            ) {
-                return Ok(String::from(spec_replicas.as_str().trim()));
-            }
+        let mut depolyment_details_per_namespace = DeploymentDetailsPerNamespace {
+            namespace: String::from(&namespace_details.namespace),
+            deployment_details: Vec::new()
+        };
+
+        for item in &namespace_details.deployment_details {
+            depolyment_details_per_namespace.deployment_details.push(process_deployment(&item.deployment, &item.details, Some(&namespace_details.pods)));
         }
 
-        Ok(String::from("undefined"))
+        depolyment_details_per_namespace
+    }
+
+    // `pods` are all pods in the deployment's namespace, the ones it selects are listed
+    pub fn process_deployment(deployment_name: &str, deployment: &Deployment, pods: Option<&[Pod]>) -> DeploymentDetailGroups {
+        DeploymentDetailGroups {
+            deployment_name: String::from(deployment_name),
+            api_version: String::from(&deployment.api_version),
+            replicas: deployment.spec.replicas,
+            kind: String::from(&deployment.kind),
+            containers: get_containers_from_spec(&deployment.spec.template.spec),
+            pods: pods.map(|pods| pods
+                .iter()
+                .filter(|pod| !pod.is_terminated())
+                .filter(|pod| deployment.spec.selector
+                    .as_ref()
+                    .is_some_and(|selector| selector.matches(&pod.metadata.labels)))
+                .map(pod_reference)
+                .collect()),
+        }
     }
 
-    pub fn get_image_from_spec(spec_string: String) -> Result<String, Error> {
-        const REGEX_PATTERN: &str = r"(image\:\s)(.*)";
-        let regex_pattern = Regex::new(REGEX_PATTERN).unwrap();
-        
-        if let Some(captures) = regex_pattern.captures(&spec_string) {
-            if let (
-                Some(spec_image),
-            ) = (
-                captures.get(2),
-            ) {
-                return Ok(String::from(spec_image.as_str().trim()));
-            }
-        }
+/**********************************************************************************************************/
 
-        Ok(String::from("undefined"))
+    pub async fn get_namespace_deployments_handler(source: &dyn ClusterSource, namespace: &str) -> Result<DeploymentDetailsPerNamespace, ReaderError> {
+
+        ensure_namespace_exists(source, namespace).await?;
+
+        let namespace_details = get_namespace_deployments(source, namespace).await?;
+
+        Ok(process_namespace_details(&namespace_details))
+    }
+
+    pub async fn get_deployment_handler(source: &dyn ClusterSource, namespace: &str, deployment_name: &str) -> Result<DeploymentDetailGroups, ReaderError> {
+
+        validate_name("deployment", deployment_name)?;
+        ensure_namespace_exists(source, namespace).await?;
+
+        let (deployment, pods) = try_join(source.get_deployment(namespace, deployment_name), source.list_pods(Some(namespace))).await?;
+
+        Ok(process_deployment(deployment_name, &deployment, Some(&pods)))
+    }
+
+    // Listing deployments in a namespace that doesn't exist succeeds with nothing in it,
+    // so the namespace is checked first to answer 404 instead
+    pub async fn ensure_namespace_exists(source: &dyn ClusterSource, namespace: &str) -> Result<(), ReaderError> {
+
+        if source.list_namespaces().await?.iter().any(|name| name == namespace) {
+            Ok(())
+        } else {
+            Err(ReaderError::not_found(format!("Namespace {} not found", namespace)))
+        }
     }
 
     #[derive(Debug)]
     #[derive(Deserialize)]
     #[derive(Serialize)]
-    pub struct Resources {
-        limits: ResourceLimits,
-        requsts: ResoruceRequests,
+    #[serde(rename_all = "lowercase")]
+    pub enum ContainerKind {
+        Init,
+        Regular,
+        Ephemeral,
     }
 
     #[derive(Debug)]
     #[derive(Deserialize)]
     #[derive(Serialize)]
-    pub struct ResourceLimits {
-        cpu: String,
-        memory: String,
+    pub struct ContainerDetails {
+        name: String,
+        kind: ContainerKind,
+        // Init container that keeps running alongside the regular containers
+        sidecar: bool,
+        image: Option<String>,
+        ports: Vec<ContainerPortDetails>,
+        resources: Resources,
     }
 
     #[derive(Debug)]
     #[derive(Deserialize)]
     #[derive(Serialize)]
-    pub struct ResoruceRequests {
-        cpu: String,
-        memory: String,
+    pub struct ContainerPortDetails {
+        name: Option<String>,
+        container_port: i32,
+        protocol: String,
+    }
+
+    pub fn get_containers_from_spec(spec: &PodSpec) -> Vec<ContainerDetails> {
+
+        let init_containers = spec.init_containers.iter().map(|container| (ContainerKind::Init, container));
+        let regular_containers = spec.containers.iter().map(|container| (ContainerKind::Regular, container));
+        let ephemeral_containers = spec.ephemeral_containers.iter().map(|container| (ContainerKind::Ephemeral, container));
+
+        init_containers
+            .chain(regular_containers)
+            .chain(ephemeral_containers)
+            .map(|(kind, container)| ContainerDetails {
+                name: String::from(&container.name),
+                sidecar: matches!(kind, ContainerKind::Init) && container.restart_policy.as_deref() == Some("Always"),
+                kind,
+                image: container.image.clone(),
+                ports: container.ports
+                    .iter()
+                    .map(|port| ContainerPortDetails {
+                        name: port.name.clone(),
+                        container_port: port.container_port,
+                        protocol: port.protocol.clone().unwrap_or_else(|| String::from("TCP")),
+                    })
+                    .collect(),
+                resources: get_resources_from_container(&container.resources),
+            })
+            .collect()
     }
 
-    pub fn get_resources_from_spec(spec_string: String) -> Result<Resources, Error> {
-
-        const REGEX_PATTERN: &str = r"(resources\:)((.*\n)+)(\s+)(securityContext\:\n)";
-        let regex_pattern = Regex::new(REGEX_PATTERN).unwrap();
-        
-        let mut resource_requests = ResoruceRequests {
-            cpu: String::new(),
-            memory: String::new(),
-        };
-
-        let mut resource_limits = ResourceLimits {
-            cpu: String::new(),
-            memory: String::new(),
-        };
-
-        if let Some(captures) = regex_pattern.captures(&spec_string) {
-            if let (
-                Some(spec_resources),
-            ) = (
-                captures.get(2),
-            ) {
-                const LIMITS_REGEX_PATTERN: &str = r"(limits\:)((.*\n)+)(\s+)(requests)";
-                let limits_regex_pattern = Regex::new(LIMITS_REGEX_PATTERN).unwrap();
-
-                if let Some(limits_captures) = limits_regex_pattern.captures(&spec_resources.as_str()) {
-                    if let (
-                        Some(limits),
-                    ) = (
-                        limits_captures.get(2),
-                    ) {
-                        let limits_memory = get_memory(String::from(limits.as_str()))?;
-                        let limits_cpu = get_cpu(String::from(limits.as_str()))?;
-                        
-                        resource_limits.cpu.push_str(&limits_cpu);
-                        resource_limits.memory.push_str(&limits_memory);
-                    }
-                }
-                
-                const REQUESTS_REGEX_PATTERN: &str = r"(requests\:)((.*\n)+.*)";
-                let requests_regex_pattern = Regex::new(REQUESTS_REGEX_PATTERN).unwrap();
-
-                if let Some(resource_captures) = requests_regex_pattern.captures(&spec_resources.as_str()) {
-                    if let (
-                        Some(requests),
-                    ) = (
-                        resource_captures.get(2),
-                    ) {
-                        let requests_memory = get_memory(String::from(requests.as_str()))?;
-                        let requests_cpu = get_cpu(String::from(requests.as_str()))?;
-
-                        resource_requests.cpu.push_str(&requests_cpu);
-                        resource_requests.memory.push_str(&requests_memory);
-                        
-                    }
-                }
-            }
-        }
-
-        let resources = Resources {
-            limits: resource_limits,
-            requsts: resource_requests,
-        };
-
-        Ok(resources)
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct Resources {
+        limits: ResourceLimits,
+        requsts: ResoruceRequests,
     }
 
-    fn get_memory(input: String) -> Result<String, Error> {
-        
-        const MEMORY_REGEX_PATTERN: &str = r"(memory\:\s)(.*)";
-        let memory_regex_pattern = Regex::new(MEMORY_REGEX_PATTERN).unwrap();
-
-        if let Some(memory_captures) = memory_regex_pattern.captures(&input.as_str()) {
-            if let (
-                Some(memory),
-            ) = (
-                memory_captures.get(2),
-            ) {
-                return Ok(String::from(memory.as_str().trim()));
-            }
-        }
-        Ok(String::from("unalbe to regex out"))
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct ResourceLimits {
+        cpu: Option<CpuQuantity>,
+        memory: Option<ByteQuantity>,
     }
 
-    fn get_cpu(input: String) -> Result<String, Error> {
-        const CPU_REGEX_PATTERN: &str = r"(cpu\:\s)(.*)";
-        let cpu_regex_pattern = Regex::new(CPU_REGEX_PATTERN).unwrap();
-
-        if let Some(cpu_captures) = cpu_regex_pattern.captures(&input.as_str()) {
-            if let (
-                Some(cpu),
-            ) = (
-                cpu_captures.get(2),
-            ) {
-                return Ok(String::from(cpu.as_str().trim()));
-            }
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct ResoruceRequests {
+        cpu: Option<CpuQuantity>,
+        memory: Option<ByteQuantity>,
+    }
+
+    pub fn get_resources_from_container(resources: &ResourceRequirements) -> Resources {
+        Resources {
+            limits: ResourceLimits {
+                cpu: resources.limits.get("cpu").map(|value| CpuQuantity::parse(value)),
+                memory: resources.limits.get("memory").map(|value| ByteQuantity::parse(value)),
+            },
+            requsts: ResoruceRequests {
+                cpu: resources.requests.get("cpu").map(|value| CpuQuantity::parse(value)),
+                memory: resources.requests.get("memory").map(|value| ByteQuantity::parse(value)),
+            },
         }
-        
-        Ok(String::from("unable to regex out"))
     }
 
 }
\ No newline at end of file
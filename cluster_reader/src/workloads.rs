@@ -0,0 +1,260 @@
+pub mod workloads {
+
+    use std::collections::BTreeMap;
+    use futures::future::{try_join, try_join_all};
+    use serde::{Deserialize, Serialize};
+
+    use crate::cluster_source::cluster_source::{ClusterSource, WorkloadKind};
+    use crate::error::error::ReaderError;
+    use crate::kube_model::kube_model::{Pod, Workload};
+    use crate::pods::pods::{pod_reference, process_owner, OwnerDetails, PodReference};
+    use crate::quantity::quantity::ByteQuantity;
+    use crate::use_case::use_case::{ensure_namespace_exists, get_containers_from_spec, ContainerDetails};
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct WorkloadDetails {
+        kind: String,
+        name: String,
+        namespace: String,
+        owner: Option<OwnerDetails>,
+        containers: Vec<ContainerDetails>,
+        // Pods the workload runs. Finished pods are only listed for jobs and cron jobs.
+        pods: Vec<PodReference>,
+        details: WorkloadSpecifics,
+    }
+
+    // What only one kind has, told apart by the kind next to it
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    #[serde(untagged)]
+    pub enum WorkloadSpecifics {
+        Deployment {
+            replicas: Option<i32>,
+            ready_replicas: Option<i32>,
+            available_replicas: Option<i32>,
+            updated_replicas: Option<i32>,
+        },
+        StatefulSet {
+            replicas: Option<i32>,
+            ready_replicas: Option<i32>,
+            updated_replicas: Option<i32>,
+            service_name: Option<String>,
+            pod_management_policy: Option<String>,
+            // Pods are named <statefulset>-<ordinal>, counting up from here
+            ordinal_start: i32,
+            volume_claim_templates: Vec<VolumeClaimTemplateDetails>,
+        },
+        DaemonSet {
+            desired_number_scheduled: Option<i32>,
+            current_number_scheduled: Option<i32>,
+            number_ready: Option<i32>,
+            number_available: Option<i32>,
+            number_misscheduled: Option<i32>,
+            node_selector: BTreeMap<String, String>,
+        },
+        ReplicaSet {
+            replicas: Option<i32>,
+            ready_replicas: Option<i32>,
+            available_replicas: Option<i32>,
+        },
+        Job {
+            completions: Option<i32>,
+            parallelism: Option<i32>,
+            backoff_limit: Option<i32>,
+            active: Option<i32>,
+            succeeded: Option<i32>,
+            failed: Option<i32>,
+            start_time: Option<String>,
+            completion_time: Option<String>,
+        },
+        CronJob {
+            schedule: String,
+            time_zone: Option<String>,
+            suspend: bool,
+            concurrency_policy: Option<String>,
+            last_schedule_time: Option<String>,
+            last_successful_time: Option<String>,
+            active_jobs: Vec<String>,
+        },
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct VolumeClaimTemplateDetails {
+        name: String,
+        storage_class: Option<String>,
+        access_modes: Vec<String>,
+        storage: Option<ByteQuantity>,
+    }
+
+    #[derive(Debug)]
+    #[derive(Deserialize)]
+    #[derive(Serialize)]
+    pub struct Workloads {
+        workloads: Vec<WorkloadDetails>,
+    }
+
+/**********************************************************************************************************/
+
+    // `kinds` are the kinds to list, all of them when empty
+    pub async fn get_workloads_handler(source: &dyn ClusterSource, kinds: &[WorkloadKind]) -> Result<Workloads, ReaderError> {
+
+        let (workloads, pods) = try_join(list_workloads(source, kinds, None), source.list_pods(None)).await?;
+
+        Ok(process_workloads(&workloads, &pods))
+    }
+
+    pub async fn get_namespace_workloads_handler(source: &dyn ClusterSource, namespace: &str, kinds: &[WorkloadKind]) -> Result<Workloads, ReaderError> {
+
+        ensure_namespace_exists(source, namespace).await?;
+
+        let (workloads, pods) = try_join(list_workloads(source, kinds, Some(namespace)), source.list_pods(Some(namespace))).await?;
+
+        Ok(process_workloads(&workloads, &pods))
+    }
+
+    pub async fn get_workload_handler(source: &dyn ClusterSource, namespace: &str, kind: WorkloadKind, workload_name: &str) -> Result<WorkloadDetails, ReaderError> {
+
+        ensure_namespace_exists(source, namespace).await?;
+
+        let (workloads, pods) = try_join(source.list_workloads(kind, Some(namespace)), source.list_pods(Some(namespace))).await?;
+
+        let workload = workloads
+            .iter()
+            .find(|workload| workload.metadata().name == workload_name)
+            .ok_or_else(|| ReaderError::not_found(format!("{} {}/{} not found", kind.object_kind(), namespace, workload_name)))?;
+
+        Ok(process_workload(kind, workload, &pods))
+    }
+
+    // For the {kind} segment of the routes, e.g. "statefulsets"
+    pub fn workload_kind(name: &str) -> Result<WorkloadKind, ReaderError> {
+        WorkloadKind::from_name(name).ok_or_else(|| {
+            let kinds: Vec<&str> = WorkloadKind::ALL.iter().map(WorkloadKind::name).collect();
+            ReaderError::invalid_request(format!("Unknown workload kind {}, expected one of {}", name, kinds.join(", ")))
+        })
+    }
+
+    async fn list_workloads(source: &dyn ClusterSource, kinds: &[WorkloadKind], namespace: Option<&str>) -> Result<Vec<(WorkloadKind, Vec<Workload>)>, ReaderError> {
+
+        let kinds = if kinds.is_empty() { &WorkloadKind::ALL[..] } else { kinds };
+
+        try_join_all(kinds
+            .iter()
+            .map(|kind| async move { Ok::<(WorkloadKind, Vec<Workload>), ReaderError>((*kind, source.list_workloads(*kind, namespace).await?)) }))
+            .await
+    }
+
+    // `pods` may span namespaces, each workload only looks at the ones in its own
+    fn process_workloads(workloads: &[(WorkloadKind, Vec<Workload>)], pods: &[Pod]) -> Workloads {
+
+        let mut workloads: Vec<WorkloadDetails> = workloads
+            .iter()
+            .flat_map(|(kind, workloads)| workloads.iter().map(|workload| process_workload(*kind, workload, pods)))
+            .collect();
+
+        workloads.sort_by(|left, right| (&left.namespace, &left.name, &left.kind).cmp(&(&right.namespace, &right.name, &right.kind)));
+
+        Workloads { workloads }
+    }
+
+    fn process_workload(kind: WorkloadKind, workload: &Workload, pods: &[Pod]) -> WorkloadDetails {
+
+        let metadata = workload.metadata();
+
+        WorkloadDetails {
+            kind: String::from(kind.object_kind()),
+            name: String::from(&metadata.name),
+            namespace: metadata.namespace.clone().unwrap_or_default(),
+            owner: process_owner(metadata),
+            containers: get_containers_from_spec(workload.pod_spec()),
+            pods: pods
+                .iter()
+                .filter(|pod| pod.metadata.namespace == metadata.namespace)
+                .filter(|pod| runs_pod(workload, pod))
+                .map(pod_reference)
+                .collect(),
+            details: process_specifics(workload),
+        }
+    }
+
+    fn runs_pod(workload: &Workload, pod: &Pod) -> bool {
+        match workload {
+            // A cron job's pods are owned by the jobs it has running
+            Workload::CronJob(cron_job) => pod.metadata
+                .controller()
+                .is_some_and(|owner| owner.kind == "Job" && cron_job.status.active
+                    .iter()
+                    .any(|job| job.name.as_deref() == Some(owner.name.as_str()))),
+            Workload::Job(_) => workload.selector().is_some_and(|selector| selector.matches(&pod.metadata.labels)),
+            _ => !pod.is_terminated() && workload.selector().is_some_and(|selector| selector.matches(&pod.metadata.labels)),
+        }
+    }
+
+    fn process_specifics(workload: &Workload) -> WorkloadSpecifics {
+        match workload {
+            Workload::Deployment(deployment) => WorkloadSpecifics::Deployment {
+                replicas: deployment.spec.replicas,
+                ready_replicas: deployment.status.ready_replicas,
+                available_replicas: deployment.status.available_replicas,
+                updated_replicas: deployment.status.updated_replicas,
+            },
+            Workload::StatefulSet(stateful_set) => WorkloadSpecifics::StatefulSet {
+                replicas: stateful_set.spec.replicas,
+                ready_replicas: stateful_set.status.ready_replicas,
+                updated_replicas: stateful_set.status.updated_replicas,
+                service_name: stateful_set.spec.service_name.clone(),
+                pod_management_policy: stateful_set.spec.pod_management_policy.clone(),
+                ordinal_start: stateful_set.spec.ordinals.as_ref().map_or(0, |ordinals| ordinals.start),
+                volume_claim_templates: stateful_set.spec.volume_claim_templates
+                    .iter()
+                    .map(|claim| VolumeClaimTemplateDetails {
+                        name: String::from(&claim.metadata.name),
+                        storage_class: claim.spec.storage_class_name.clone(),
+                        access_modes: claim.spec.access_modes.clone(),
+                        storage: claim.spec.resources.requests.get("storage").map(|storage| ByteQuantity::parse(storage)),
+                    })
+                    .collect(),
+            },
+            Workload::DaemonSet(daemon_set) => WorkloadSpecifics::DaemonSet {
+                desired_number_scheduled: daemon_set.status.desired_number_scheduled,
+                current_number_scheduled: daemon_set.status.current_number_scheduled,
+                number_ready: daemon_set.status.number_ready,
+                number_available: daemon_set.status.number_available,
+                number_misscheduled: daemon_set.status.number_misscheduled,
+                node_selector: daemon_set.spec.template.spec.node_selector.clone(),
+            },
+            Workload::ReplicaSet(replica_set) => WorkloadSpecifics::ReplicaSet {
+                replicas: replica_set.spec.replicas,
+                ready_replicas: replica_set.status.ready_replicas,
+                available_replicas: replica_set.status.available_replicas,
+            },
+            Workload::Job(job) => WorkloadSpecifics::Job {
+                completions: job.spec.completions,
+                parallelism: job.spec.parallelism,
+                backoff_limit: job.spec.backoff_limit,
+                active: job.status.active,
+                succeeded: job.status.succeeded,
+                failed: job.status.failed,
+                start_time: job.status.start_time.clone(),
+                completion_time: job.status.completion_time.clone(),
+            },
+            Workload::CronJob(cron_job) => WorkloadSpecifics::CronJob {
+                schedule: String::from(&cron_job.spec.schedule),
+                time_zone: cron_job.spec.time_zone.clone(),
+                suspend: cron_job.spec.suspend.unwrap_or(false),
+                concurrency_policy: cron_job.spec.concurrency_policy.clone(),
+                last_schedule_time: cron_job.status.last_schedule_time.clone(),
+                last_successful_time: cron_job.status.last_successful_time.clone(),
+                active_jobs: cron_job.status.active
+                    .iter()
+                    .filter_map(|job| job.name.clone())
+                    .collect(),
+            },
+        }
+    }
+}